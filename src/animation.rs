@@ -0,0 +1,419 @@
+use crate::color::Rgb;
+use crate::objects::Object;
+use crate::objects::cube::euler_rotation;
+use crate::renderer::PointLight;
+use glm::{Mat3, Vec3, vec3};
+use nalgebra_glm as glm;
+use std::f32::consts::PI;
+
+// Valor escalar en función del tiempo (segundos de escena).
+#[derive(Clone, Debug)]
+pub enum Curve {
+    // Keyframes (t, valor) interpolados linealmente; fuera del rango se mantienen los extremos.
+    Keys(Vec<(f32, f32)>),
+    // Ángulo que crece sin fin (giro).
+    Spin {
+        deg_per_sec: f32,
+    },
+    // Seno: sirve para flotar (bob) y para pulsos.
+    Wave {
+        base: f32,
+        amplitude: f32,
+        freq: f32,
+    },
+    // Ruido suave tipo llama: `base` ± `amount`.
+    Flicker {
+        base: f32,
+        amount: f32,
+        speed: f32,
+    },
+}
+
+fn hash(i: i32) -> f32 {
+    let mut x = (i as u32).wrapping_mul(0x9E37_79B9);
+    x ^= x >> 16;
+    x = x.wrapping_mul(0x85EB_CA6B);
+    x ^= x >> 13;
+    (x & 0xFFFF) as f32 / 65535.0 * 2.0 - 1.0
+}
+
+// Ruido de valor 1D interpolado con smoothstep, en [-1, 1].
+fn value_noise(t: f32) -> f32 {
+    let i = t.floor();
+    let f = t - i;
+    let f = f * f * (3.0 - 2.0 * f);
+    hash(i as i32) * (1.0 - f) + hash(i as i32 + 1) * f
+}
+
+impl Curve {
+    pub fn eval(&self, t: f32) -> f32 {
+        match self {
+            Curve::Keys(keys) => {
+                let Some(&(t0, v0)) = keys.first() else {
+                    return 0.0;
+                };
+                if t <= t0 {
+                    return v0;
+                }
+                for w in keys.windows(2) {
+                    let ((ta, va), (tb, vb)) = (w[0], w[1]);
+                    if t <= tb {
+                        let s = if tb > ta { (t - ta) / (tb - ta) } else { 1.0 };
+                        return va + (vb - va) * s;
+                    }
+                }
+                keys.last().map_or(0.0, |k| k.1)
+            }
+            Curve::Spin { deg_per_sec } => deg_per_sec * t,
+            Curve::Wave {
+                base,
+                amplitude,
+                freq,
+            } => base + amplitude * (2.0 * PI * freq * t).sin(),
+            Curve::Flicker {
+                base,
+                amount,
+                speed,
+            } => {
+                let n = value_noise(t * speed) * 0.7 + value_noise(t * speed * 2.3 + 17.0) * 0.3;
+                base + amount * n
+            }
+        }
+    }
+}
+
+// Qué propiedad mueve una pista. Los índices de la pista son de `objects` o de `lights`.
+#[derive(Clone, Debug)]
+pub enum Channel {
+    // Desplazamiento del cubo desde su posición de reposo, a lo largo del eje dado.
+    Offset(Vec3),
+    // Ángulos (yaw, pitch, roll) en grados = eje * valor.
+    Rotate(Vec3),
+    LightIntensity,
+    // Mezcla entre dos colores; la curva va de 0 a 1.
+    LightColor(Rgb, Rgb),
+    Emission,
+    Ripple,
+}
+
+#[derive(Clone, Debug)]
+pub struct Track {
+    pub channel: Channel,
+    pub curve: Curve,
+    pub targets: Vec<usize>,
+    // Desfase en segundos entre objetivos consecutivos, para que no se muevan al unísono.
+    pub phase_step: f32,
+}
+
+impl Track {
+    pub fn new(channel: Channel, curve: Curve, targets: Vec<usize>) -> Self {
+        Self {
+            channel,
+            curve,
+            targets,
+            phase_step: 0.0,
+        }
+    }
+
+    pub fn with_phase_step(mut self, step: f32) -> Self {
+        self.phase_step = step;
+        self
+    }
+
+    fn is_transform(&self) -> bool {
+        matches!(self.channel, Channel::Offset(_) | Channel::Rotate(_))
+    }
+
+    fn targets_lights(&self) -> bool {
+        matches!(
+            self.channel,
+            Channel::LightIntensity | Channel::LightColor(_, _)
+        )
+    }
+}
+
+// Lugares (pista, posición) que ocupaba un objeto o luz borrado, para deshacer.
+#[derive(Clone, Debug, Default)]
+pub struct TrackSlots {
+    slots: Vec<(usize, usize)>,
+    rest: Option<(Vec3, Mat3)>,
+}
+
+fn rest_pose(object: &Object) -> Option<(Vec3, Mat3)> {
+    match object {
+        Object::Cube(c) => Some((c.center, c.rot)),
+        _ => None,
+    }
+}
+
+// Línea de tiempo: pistas aplicadas sobre la escena a partir de su estado de reposo.
+#[derive(Clone, Debug, Default)]
+pub struct Timeline {
+    pub tracks: Vec<Track>,
+    // Centro y rotación de reposo de cada cubo (`None` para los demás objetos).
+    rest: Vec<Option<(Vec3, Mat3)>>,
+}
+
+impl Timeline {
+    // Guarda la pose de reposo de los cubos; las pistas se suman sobre ella.
+    pub fn new(objects: &[Object], tracks: Vec<Track>) -> Self {
+        let rest = objects.iter().map(rest_pose).collect();
+        Self { tracks, rest }
+    }
+
+    pub fn rest_center(&self, index: usize) -> Option<Vec3> {
+        self.rest.get(index).copied().flatten().map(|(c, _)| c)
+    }
+
+    pub fn rest_rotation(&self, index: usize) -> Option<Mat3> {
+        self.rest.get(index).copied().flatten().map(|(_, r)| r)
+    }
+
+    // Mantiene los índices de las pistas al borrar/insertar en `objects` o `lights`.
+    fn remove_index(&mut self, index: usize, lights: bool) -> TrackSlots {
+        let mut slots = vec![];
+        for (ti, tr) in self.tracks.iter_mut().enumerate() {
+            if tr.targets_lights() != lights {
+                continue;
+            }
+            if let Some(k) = tr.targets.iter().position(|&i| i == index) {
+                tr.targets.remove(k);
+                slots.push((ti, k));
+            }
+            for i in tr.targets.iter_mut().filter(|i| **i > index) {
+                *i -= 1;
+            }
+        }
+        TrackSlots { slots, rest: None }
+    }
+
+    fn insert_index(&mut self, index: usize, lights: bool, slots: &TrackSlots) {
+        for tr in self
+            .tracks
+            .iter_mut()
+            .filter(|tr| tr.targets_lights() == lights)
+        {
+            for i in tr.targets.iter_mut().filter(|i| **i >= index) {
+                *i += 1;
+            }
+        }
+        for &(ti, k) in slots.slots.iter() {
+            if let Some(tr) = self.tracks.get_mut(ti) {
+                tr.targets.insert(k.min(tr.targets.len()), index);
+            }
+        }
+    }
+
+    pub fn remove_object(&mut self, index: usize) -> TrackSlots {
+        let mut slots = self.remove_index(index, false);
+        if index < self.rest.len() {
+            slots.rest = self.rest.remove(index);
+        }
+        slots
+    }
+
+    // `slots` vacío para objetos nuevos (quedan sin animar).
+    pub fn insert_object(&mut self, index: usize, object: &Object, slots: &TrackSlots) {
+        self.insert_index(index, false, slots);
+        let rest = slots.rest.or(rest_pose(object));
+        let index = index.min(self.rest.len());
+        self.rest.insert(index, rest);
+    }
+
+    pub fn remove_light(&mut self, index: usize) -> TrackSlots {
+        self.remove_index(index, true)
+    }
+
+    pub fn insert_light(&mut self, index: usize, slots: &TrackSlots) {
+        self.insert_index(index, true, slots);
+    }
+
+    // (centro, rotación) del cubo `index` en `t`; `None` si ninguna pista lo mueve. Los
+    // giros de las pistas se aplican sobre la rotación de reposo, en ejes del mundo.
+    fn cube_pose(&self, index: usize, t: f32) -> Option<(Vec3, Option<Mat3>)> {
+        let (mut center, rest_rot) = (*self.rest.get(index)?)?;
+        let mut angles: Option<Vec3> = None;
+        let mut moved = false;
+        for tr in self.tracks.iter().filter(|tr| tr.is_transform()) {
+            let Some(k) = tr.targets.iter().position(|&i| i == index) else {
+                continue;
+            };
+            let v = tr.curve.eval(t + k as f32 * tr.phase_step);
+            moved = true;
+            match tr.channel {
+                Channel::Offset(axis) => center += axis * v,
+                Channel::Rotate(axis) => {
+                    *angles.get_or_insert(vec3(0.0, 0.0, 0.0)) += axis * v;
+                }
+                _ => {}
+            }
+        }
+        let rot = angles.map(|a| euler_rotation(a.x, a.y, a.z) * rest_rot);
+        moved.then_some((center, rot))
+    }
+
+    pub fn apply(&self, objects: &mut [Object], lights: &mut [PointLight], t: f32) {
+        self.apply_shutter(objects, lights, t, t);
+    }
+
+    // Estado en `t_open`; los cubos animados guardan además su pose en `t_close`
+    // para el motion blur. Con `t_open == t_close` no queda movimiento.
+    pub fn apply_shutter(
+        &self,
+        objects: &mut [Object],
+        lights: &mut [PointLight],
+        t_open: f32,
+        t_close: f32,
+    ) {
+        for (index, o) in objects.iter_mut().enumerate() {
+            let Object::Cube(cube) = o else {
+                continue;
+            };
+            let Some((center, rot)) = self.cube_pose(index, t_open) else {
+                continue;
+            };
+            cube.center = center;
+            if let Some(r) = rot {
+                cube.set_rotation(r);
+            }
+            cube.motion = None;
+            if t_close != t_open
+                && let Some((end, end_rot)) = self.cube_pose(index, t_close)
+            {
+                cube.set_motion(end, end_rot);
+            }
+        }
+
+        for tr in self.tracks.iter().filter(|tr| !tr.is_transform()) {
+            for (k, &i) in tr.targets.iter().enumerate() {
+                let v = tr.curve.eval(t_open + k as f32 * tr.phase_step);
+                match tr.channel {
+                    Channel::LightIntensity => {
+                        if let Some(l) = lights.get_mut(i) {
+                            l.intensity = v.max(0.0);
+                        }
+                    }
+                    Channel::LightColor(a, b) => {
+                        if let Some(l) = lights.get_mut(i) {
+                            l.color = Rgb::lerp(a, b, v.clamp(0.0, 1.0));
+                        }
+                    }
+                    Channel::Emission => {
+                        if let Some(o) = objects.get_mut(i) {
+                            o.material_mut().emission_strength = v.max(0.0);
+                        }
+                    }
+                    Channel::Ripple => {
+                        if let Some(o) = objects.get_mut(i) {
+                            o.material_mut().ripple = v.max(0.0);
+                        }
+                    }
+                    Channel::Offset(_) | Channel::Rotate(_) => {}
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::color::Material;
+    use crate::objects::{Cube, Plane};
+
+    fn cube(x: f32, yaw: f32) -> Object {
+        Object::Cube(Cube::from_center_size_rot(
+            vec3(x, 0.0, 0.0),
+            1.0,
+            yaw,
+            0.0,
+            0.0,
+            Material::solid(Rgb::new(200, 60, 50)),
+        ))
+    }
+
+    fn scene() -> (Vec<Object>, Timeline) {
+        let objects = vec![
+            Object::Plane(Plane::new(
+                vec3(0.0, -1.0, 0.0),
+                vec3(0.0, 1.0, 0.0),
+                Material::solid(Rgb::new(120, 170, 90)),
+            )),
+            cube(1.0, 0.0),
+            cube(2.0, 0.0),
+            cube(3.0, 0.0),
+        ];
+        let tracks = vec![
+            Track::new(
+                Channel::Offset(vec3(0.0, 1.0, 0.0)),
+                Curve::Spin { deg_per_sec: 1.0 },
+                vec![1, 3, 2],
+            ),
+            Track::new(
+                Channel::LightIntensity,
+                Curve::Spin { deg_per_sec: 1.0 },
+                vec![0, 1],
+            ),
+        ];
+        let timeline = Timeline::new(&objects, tracks);
+        (objects, timeline)
+    }
+
+    #[test]
+    fn borrar_corre_los_indices_y_deshacer_los_repone() {
+        let (objects, mut timeline) = scene();
+        let slots = timeline.remove_object(2);
+        assert_eq!(timeline.tracks[0].targets, vec![1, 2]);
+        assert_eq!(timeline.tracks[1].targets, vec![0, 1]);
+        assert_eq!(timeline.rest_center(2), Some(vec3(3.0, 0.0, 0.0)));
+
+        timeline.insert_object(2, &objects[2], &slots);
+        assert_eq!(timeline.tracks[0].targets, vec![1, 3, 2]);
+        assert_eq!(timeline.rest_center(2), Some(vec3(2.0, 0.0, 0.0)));
+        assert_eq!(timeline.rest_center(3), Some(vec3(3.0, 0.0, 0.0)));
+        assert_eq!(timeline.rest_center(0), None);
+    }
+
+    #[test]
+    fn el_reposo_vuelve_aunque_el_objeto_se_haya_movido() {
+        let (mut objects, mut timeline) = scene();
+        timeline.apply(&mut objects, &mut [], 5.0);
+        let slots = timeline.remove_object(1);
+        let moved = objects.remove(1);
+        timeline.insert_object(1, &moved, &slots);
+        assert_eq!(timeline.rest_center(1), Some(vec3(1.0, 0.0, 0.0)));
+    }
+
+    #[test]
+    fn borrar_una_luz_solo_toca_las_pistas_de_luces() {
+        let (_, mut timeline) = scene();
+        let slots = timeline.remove_light(0);
+        assert_eq!(timeline.tracks[1].targets, vec![0]);
+        assert_eq!(timeline.tracks[0].targets, vec![1, 3, 2]);
+        timeline.insert_light(0, &slots);
+        assert_eq!(timeline.tracks[1].targets, vec![0, 1]);
+
+        // Una luz nueva al principio corre las demás sin quedar animada.
+        timeline.insert_light(0, &TrackSlots::default());
+        assert_eq!(timeline.tracks[1].targets, vec![1, 2]);
+    }
+
+    #[test]
+    fn el_giro_se_suma_a_la_rotacion_de_reposo() {
+        let mut objects = vec![cube(0.0, 30.0)];
+        let track = Track::new(
+            Channel::Rotate(vec3(1.0, 0.0, 0.0)),
+            Curve::Keys(vec![(0.0, 0.0), (1.0, 60.0)]),
+            vec![0],
+        );
+        let timeline = Timeline::new(&objects, vec![track]);
+        for (t, yaw) in [(0.0, 30.0), (1.0, 90.0)] {
+            timeline.apply(&mut objects, &mut [], t);
+            let Object::Cube(c) = &objects[0] else {
+                unreachable!()
+            };
+            let expected = euler_rotation(yaw, 0.0, 0.0);
+            assert!((c.rot - expected).abs().max() < 1e-5, "t = {t}");
+        }
+    }
+}
@@ -0,0 +1,223 @@
+use crate::color::Rgb;
+use crate::error::Error;
+use crate::formats::exr;
+use crate::framebuffer::FrameBuffer;
+use glm::{Vec3, vec3};
+use nalgebra_glm as glm;
+
+// Pasadas que salen de un mismo render. Los colores van en la escala del render
+// (1.0 = 255) y sin recortar; `depth` es la distancia al impacto (infinito en el cielo).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Pass {
+    Beauty,
+    Albedo,
+    Normal,
+    Depth,
+    DiffuseDirect,
+    SpecularDirect,
+    Reflection,
+    Refraction,
+    Emission,
+    Shadow,
+}
+
+impl Pass {
+    pub const ALL: [(Pass, &'static str); 10] = [
+        (Pass::Beauty, "beauty"),
+        (Pass::Albedo, "albedo"),
+        (Pass::Normal, "normal"),
+        (Pass::Depth, "depth"),
+        (Pass::DiffuseDirect, "diffuse"),
+        (Pass::SpecularDirect, "specular"),
+        (Pass::Reflection, "reflection"),
+        (Pass::Refraction, "refraction"),
+        (Pass::Emission, "emission"),
+        (Pass::Shadow, "shadow"),
+    ];
+
+    fn is_scalar(self) -> bool {
+        matches!(self, Pass::Depth | Pass::Shadow)
+    }
+
+    // Se guardan en EXR aunque se exporte a carpeta: no caben en 8 bits.
+    fn needs_float(self) -> bool {
+        matches!(self, Pass::Normal | Pass::Depth)
+    }
+}
+
+// Valores de todas las pasadas para un píxel.
+// beauty ≈ diffuse + specular + emission + reflection + refraction + ambiente (IBL),
+// con las partes ya pesadas por la mezcla de reflexión/refracción de `shade`.
+#[derive(Clone, Copy, Debug)]
+pub struct AovSample {
+    pub beauty: Vec3,
+    pub albedo: Vec3,
+    pub normal: Vec3,
+    pub depth: f32,
+    pub diffuse: Vec3,
+    pub specular: Vec3,
+    pub reflection: Vec3,
+    pub refraction: Vec3,
+    pub emission: Vec3,
+    // Fracción de luces visibles (1 = sin sombra).
+    pub shadow: f32,
+}
+
+impl Default for AovSample {
+    fn default() -> Self {
+        let zero = vec3(0.0, 0.0, 0.0);
+        Self {
+            beauty: zero,
+            albedo: zero,
+            normal: zero,
+            depth: f32::INFINITY,
+            diffuse: zero,
+            specular: zero,
+            reflection: zero,
+            refraction: zero,
+            emission: zero,
+            shadow: 1.0,
+        }
+    }
+}
+
+impl AovSample {
+    // Equivale a `col = lerp(col, otro, t)` sobre lo acumulado hasta ahora.
+    pub fn fade(&mut self, t: f32) {
+        let k = 1.0 - t;
+        self.diffuse *= k;
+        self.specular *= k;
+        self.reflection *= k;
+        self.refraction *= k;
+        self.emission *= k;
+    }
+
+    pub fn get(&self, pass: Pass) -> Vec3 {
+        match pass {
+            Pass::Beauty => self.beauty,
+            Pass::Albedo => self.albedo,
+            Pass::Normal => self.normal,
+            Pass::Depth => vec3(self.depth, self.depth, self.depth),
+            Pass::DiffuseDirect => self.diffuse,
+            Pass::SpecularDirect => self.specular,
+            Pass::Reflection => self.reflection,
+            Pass::Refraction => self.refraction,
+            Pass::Emission => self.emission,
+            Pass::Shadow => vec3(self.shadow, self.shadow, self.shadow),
+        }
+    }
+
+    // Promedio de varias muestras (motion blur).
+    pub fn average(samples: &[AovSample]) -> AovSample {
+        let n = samples.len().max(1) as f32;
+        let mut out = AovSample {
+            depth: f32::INFINITY,
+            shadow: 0.0,
+            ..AovSample::default()
+        };
+        for s in samples {
+            out.beauty += s.beauty / n;
+            out.albedo += s.albedo / n;
+            out.normal += s.normal / n;
+            out.diffuse += s.diffuse / n;
+            out.specular += s.specular / n;
+            out.reflection += s.reflection / n;
+            out.refraction += s.refraction / n;
+            out.emission += s.emission / n;
+            out.shadow += s.shadow / n;
+            // La profundidad no se promedia: se queda la más cercana.
+            out.depth = out.depth.min(s.depth);
+        }
+        out
+    }
+}
+
+pub struct AovBuffer {
+    pub width: i32,
+    pub height: i32,
+    pub pixels: Vec<AovSample>,
+}
+
+impl AovBuffer {
+    pub fn new(width: i32, height: i32) -> Self {
+        Self {
+            width,
+            height,
+            pixels: vec![AovSample::default(); (width * height).max(0) as usize],
+        }
+    }
+
+    pub fn set(&mut self, x: i32, y: i32, s: AovSample) {
+        if (0..self.width).contains(&x) && (0..self.height).contains(&y) {
+            self.pixels[(y * self.width + x) as usize] = s;
+        }
+    }
+
+    // Canales EXR de una pasada: `capa.R/G/B` o `capa.Z` para las escalares.
+    // La beauty va en R/G/B sin prefijo para que los visores la muestren por defecto.
+    fn channels(&self, pass: Pass, name: &str) -> Vec<(String, Vec<f32>)> {
+        let prefix = if pass == Pass::Beauty {
+            String::new()
+        } else {
+            format!("{name}.")
+        };
+        let values = |k: usize| self.pixels.iter().map(|p| p.get(pass)[k]).collect();
+        if pass.is_scalar() {
+            let suffix = if pass == Pass::Depth { "Z" } else { "Y" };
+            vec![(format!("{prefix}{suffix}"), values(0))]
+        } else {
+            ["R", "G", "B"]
+                .iter()
+                .enumerate()
+                .map(|(k, c)| (format!("{prefix}{c}"), values(k)))
+                .collect()
+        }
+    }
+
+    // Un solo EXR con todas las pasadas como capas.
+    pub fn write_exr(&self, path: &str) -> Result<(), Error> {
+        let channels: Vec<_> = Pass::ALL
+            .iter()
+            .flat_map(|(pass, name)| self.channels(*pass, name))
+            .collect();
+        let bytes = exr::encode(self.width as usize, self.height as usize, &channels);
+        std::fs::write(path, bytes).map_err(|e| Error::write(path, e))
+    }
+
+    // Carpeta con un archivo por pasada: PNG de 8 bits, salvo normal y depth en EXR.
+    pub fn write_folder(&self, dir: &str) -> Result<(), Error> {
+        std::fs::create_dir_all(dir).map_err(|e| Error::write(dir, e))?;
+        for (pass, name) in Pass::ALL.iter() {
+            if pass.needs_float() {
+                let path = format!("{dir}/{name}.exr");
+                let mut channels = self.channels(*pass, name);
+                // Sin prefijo de capa: cada archivo es una sola imagen.
+                for (n, _) in channels.iter_mut() {
+                    *n = n.rsplit('.').next().unwrap_or(n).to_string();
+                }
+                let bytes = exr::encode(self.width as usize, self.height as usize, &channels);
+                std::fs::write(&path, bytes).map_err(|e| Error::write(&path, e))?;
+                continue;
+            }
+            let mut fb = FrameBuffer::new(self.width, self.height, Rgb::new(0, 0, 0));
+            for y in 0..self.height {
+                for x in 0..self.width {
+                    let v = self.pixels[(y * self.width + x) as usize].get(*pass);
+                    let f = |c: f32| (c * 255.0).round().clamp(0.0, 255.0) as u8;
+                    fb.set_pixel_color(x, y, Rgb::new(f(v.x), f(v.y), f(v.z)));
+                }
+            }
+            fb.render_to_file(&format!("{dir}/{name}.png"))?;
+        }
+        Ok(())
+    }
+
+    // `.exr` → multicapa; cualquier otra ruta se toma como carpeta.
+    pub fn write(&self, dest: &str) -> Result<(), Error> {
+        if dest.to_lowercase().ends_with(".exr") {
+            self.write_exr(dest)
+        } else {
+            self.write_folder(dest)
+        }
+    }
+}
@@ -0,0 +1,162 @@
+// Render offline del diorama, sin ventana:
+//   offline                      la vista inicial en `--salida` (render.png)
+//   offline --aov destino        las AOV de la vista inicial
+//   offline --render-path dir    el recorrido de `--path` cuadro por cuadro
+// Acepta las mismas opciones de cámara, escena, cielo, modo y denoiser que el visor.
+use proyect2_raytracing::aov;
+use proyect2_raytracing::camera_path::CameraPath;
+use proyect2_raytracing::color::Rgb;
+use proyect2_raytracing::denoise::Denoiser;
+use proyect2_raytracing::framebuffer::FrameBuffer;
+use proyect2_raytracing::renderer;
+use proyect2_raytracing::scene::Scene;
+
+#[path = "../cli.rs"]
+mod cli;
+#[path = "../diorama.rs"]
+mod diorama;
+
+use cli::arg_value;
+
+fn main() {
+    // `--ancho` y `--alto`: tamaño de salida (el de la ventana del visor por defecto).
+    let width: i32 = arg_value("--ancho")
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(960)
+        .max(1);
+    let height: i32 = arg_value("--alto")
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(540)
+        .max(1);
+
+    let mut cam = diorama::camera(width as f32 / height as f32);
+    cli::camera_options(&mut cam);
+
+    let mut palette = diorama::palette(cli::strict_textures()).unwrap_or_else(|e| {
+        eprintln!("{e}");
+        std::process::exit(1);
+    });
+    let diorama_objects = diorama::objects(&palette);
+    let (_, objs) = cli::scene(&mut palette, diorama_objects);
+    let lights = diorama::lights(&objs);
+    let timeline = diorama::timeline(&objs, &lights);
+    let mut scene = Scene::builder()
+        .with_objects(objs)
+        .with_lights(lights)
+        .with_environment(diorama::sky(&cli::sky_path()))
+        .build();
+    cli::fog_options(&mut scene.fog);
+    cli::render_options(&mut scene.settings);
+    let mut denoiser = Denoiser::default();
+    cli::denoiser_options(&mut denoiser);
+
+    // ===================== AOV de la vista inicial =====================
+    // `--aov destino` renderiza las pasadas (beauty, albedo, normal, depth, ...).
+    // `destino.exr` da un EXR multicapa; otra ruta se toma como carpeta con una imagen
+    // por pasada. Con `--render-path` se escribe además `aov_0000.exr`... junto a cada cuadro.
+    let aov_dest = arg_value("--aov");
+    let render_path = arg_value("--render-path");
+    if let (Some(dest), None) = (aov_dest.as_ref(), render_path.as_ref()) {
+        cam.set_aspect(width as f32 / height as f32);
+        let mut aovs = aov::AovBuffer::new(width, height);
+        renderer::render_aovs(&cam, &mut aovs, &scene);
+        match aovs.write(dest) {
+            Ok(()) => println!("AOV guardadas en {dest}"),
+            Err(e) => {
+                eprintln!("No pude guardar las AOV ({e})");
+                std::process::exit(1);
+            }
+        }
+        return;
+    }
+
+    let (out_w, out_h) = cam.output_size(width, height);
+    let mut fb = FrameBuffer::new(out_w, out_h, Rgb::new(0, 0, 0));
+    cam.set_aspect(out_w as f32 / out_h as f32);
+
+    // ===================== Vista inicial =====================
+    let Some(out_dir) = render_path else {
+        let file = arg_value("--salida").unwrap_or_else(|| "render.png".to_string());
+        renderer::render_to_fb(&cam, &mut fb, &scene);
+        if denoiser.enabled {
+            let mut features = aov::AovBuffer::new(out_w, out_h);
+            renderer::render_features(&cam, &mut features, &scene);
+            denoiser.apply(&mut fb, &features);
+        }
+        match fb.render_to_file(&file) {
+            Ok(()) => println!("Render guardado en {file}"),
+            Err(e) => {
+                eprintln!("{e}");
+                std::process::exit(1);
+            }
+        }
+        return;
+    };
+
+    // ===================== Recorrido =====================
+    // `--render-path carpeta` renderiza el recorrido de `--path` (camera_path.txt) como
+    // `frame_0000.png`, `frame_0001.png`... a `--fps` cuadros por segundo (24).
+    let path_file = arg_value("--path").unwrap_or_else(|| "camera_path.txt".to_string());
+    let cam_path = CameraPath::load(&path_file).unwrap_or_else(|e| {
+        eprintln!("{e}");
+        std::process::exit(1);
+    });
+    if cam_path.keys.is_empty() {
+        eprintln!("{path_file}: el recorrido no tiene keyframes");
+        std::process::exit(1);
+    }
+    if let Err(e) = std::fs::create_dir_all(&out_dir) {
+        eprintln!("{out_dir}: {e}");
+        std::process::exit(1);
+    }
+    let fps = arg_value("--fps")
+        .and_then(|v| v.parse::<f32>().ok())
+        .unwrap_or(24.0)
+        .max(1.0);
+    let frames = (cam_path.duration() * fps).round() as i32 + 1;
+    // `--obturador f`: fracción del cuadro con el obturador abierto (motion blur),
+    // promediando `--muestras-movimiento` instantes (8 por defecto).
+    let shutter = arg_value("--obturador")
+        .and_then(|v| v.parse::<f32>().ok())
+        .unwrap_or(0.0)
+        .clamp(0.0, 1.0);
+    if shutter > 0.0 {
+        scene.settings.motion_samples = arg_value("--muestras-movimiento")
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(8);
+    }
+    for i in 0..frames {
+        let t = i as f32 / fps;
+        cam_path.apply(&mut cam, t);
+        let (objects, lights) = scene.edit();
+        timeline.apply_shutter(objects, lights, t - shutter / fps, t);
+        scene.settings.scene_time = t;
+        scene.commit();
+        if shutter > 0.0 {
+            cam.shutter_open = cam_path
+                .sample(t - shutter / fps)
+                .map(|k| (k.pos, k.yaw, k.pitch));
+        }
+        renderer::render_to_fb(&cam, &mut fb, &scene);
+        if denoiser.enabled {
+            let mut features = aov::AovBuffer::new(out_w, out_h);
+            renderer::render_features(&cam, &mut features, &scene);
+            denoiser.apply(&mut fb, &features);
+        }
+        let file = format!("{out_dir}/frame_{i:04}.png");
+        if let Err(e) = fb.render_to_file(&file) {
+            eprintln!("{e}");
+            std::process::exit(1);
+        }
+        if aov_dest.is_some() {
+            let mut aovs = aov::AovBuffer::new(out_w, out_h);
+            renderer::render_aovs(&cam, &mut aovs, &scene);
+            let file = format!("{out_dir}/aov_{i:04}.exr");
+            if let Err(e) = aovs.write_exr(&file) {
+                eprintln!("{e}");
+                std::process::exit(1);
+            }
+        }
+        println!("Cuadro {} / {frames}", i + 1);
+    }
+}
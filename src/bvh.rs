@@ -0,0 +1,216 @@
+// Jerarquía de cajas (BVH) sobre los objetos de una escena. Los objetos sin caja
+// (planos) quedan aparte y se prueban siempre.
+use crate::objects::Object;
+use glm::{Vec3, vec3};
+use nalgebra_glm as glm;
+use std::ops::ControlFlow;
+
+// Objetos por hoja; con menos no conviene seguir dividiendo.
+const LEAF_SIZE: usize = 4;
+// Margen de las cajas: un rayo rasante a una cara no se pierde por redondeo.
+const PAD: f32 = 1e-4;
+// Profundidad máxima del recorrido; con cortes por mediana alcanza para millones de objetos.
+const STACK_SIZE: usize = 64;
+
+#[derive(Clone, Copy, Debug)]
+struct Aabb {
+    min: Vec3,
+    max: Vec3,
+}
+
+impl Aabb {
+    fn empty() -> Self {
+        Self {
+            min: vec3(f32::INFINITY, f32::INFINITY, f32::INFINITY),
+            max: vec3(f32::NEG_INFINITY, f32::NEG_INFINITY, f32::NEG_INFINITY),
+        }
+    }
+
+    fn union(&self, other: &Aabb) -> Aabb {
+        Aabb {
+            min: self.min.inf(&other.min),
+            max: self.max.sup(&other.max),
+        }
+    }
+
+    fn grow(&self, p: &Vec3) -> Aabb {
+        Aabb {
+            min: self.min.inf(p),
+            max: self.max.sup(p),
+        }
+    }
+
+    fn centroid(&self) -> Vec3 {
+        (self.min + self.max) * 0.5
+    }
+
+    // El rayo (con `inv_rd` = 1 / dirección) entra a la caja antes de `t_max`.
+    fn hit(&self, ro: &Vec3, inv_rd: &Vec3, t_max: f32) -> bool {
+        let mut t0 = 0.0f32;
+        let mut t1 = t_max;
+        for axis in 0..3 {
+            let a = (self.min[axis] - ro[axis]) * inv_rd[axis];
+            let b = (self.max[axis] - ro[axis]) * inv_rd[axis];
+            t0 = t0.max(a.min(b));
+            t1 = t1.min(a.max(b));
+        }
+        t0 <= t1
+    }
+}
+
+// Hoja: `count` objetos desde `first` en `order`. Interna (`count` = 0): hijos en
+// `first` y `first + 1`, cortados sobre `axis`.
+#[derive(Clone, Copy, Debug)]
+struct Node {
+    bounds: Aabb,
+    first: usize,
+    count: usize,
+    axis: usize,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct Bvh {
+    nodes: Vec<Node>,
+    order: Vec<usize>,
+    unbounded: Vec<usize>,
+    len: usize,
+}
+
+impl Bvh {
+    pub fn build(objects: &[Object]) -> Self {
+        let mut boxes = vec![Aabb::empty(); objects.len()];
+        let mut order = vec![];
+        let mut unbounded = vec![];
+        for (i, o) in objects.iter().enumerate() {
+            match o.bounds() {
+                Some((min, max)) => {
+                    let pad = vec3(PAD, PAD, PAD);
+                    boxes[i] = Aabb {
+                        min: min - pad,
+                        max: max + pad,
+                    };
+                    order.push(i);
+                }
+                None => unbounded.push(i),
+            }
+        }
+        let mut bvh = Self {
+            nodes: vec![],
+            order,
+            unbounded,
+            len: objects.len(),
+        };
+        if !bvh.order.is_empty() {
+            bvh.nodes.push(Node {
+                bounds: Aabb::empty(),
+                first: 0,
+                count: 0,
+                axis: 0,
+            });
+            bvh.split(0, 0, bvh.order.len(), &boxes);
+        }
+        bvh
+    }
+
+    // Cantidad de objetos de la escena con la que se armó.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    // Arma el nodo `node` con `order[start..end]`: hoja si son pocos, o corte por la
+    // mediana de los centros sobre el eje más largo.
+    fn split(&mut self, node: usize, start: usize, end: usize, boxes: &[Aabb]) {
+        let items = &mut self.order[start..end];
+        let bounds = items.iter().fold(Aabb::empty(), |b, &i| b.union(&boxes[i]));
+        let centers = items
+            .iter()
+            .fold(Aabb::empty(), |b, &i| b.grow(&boxes[i].centroid()));
+        let extent = centers.max - centers.min;
+        let axis = if extent.x >= extent.y && extent.x >= extent.z {
+            0
+        } else if extent.y >= extent.z {
+            1
+        } else {
+            2
+        };
+        // Pocos objetos o todos con el mismo centro: hoja.
+        if items.len() <= LEAF_SIZE || extent[axis] <= 0.0 {
+            self.nodes[node] = Node {
+                bounds,
+                first: start,
+                count: items.len(),
+                axis,
+            };
+            return;
+        }
+        let mid = items.len() / 2;
+        items.select_nth_unstable_by(mid, |&a, &b| {
+            boxes[a].centroid()[axis].total_cmp(&boxes[b].centroid()[axis])
+        });
+        let left = self.nodes.len();
+        let placeholder = self.nodes[node];
+        self.nodes.push(placeholder);
+        self.nodes.push(placeholder);
+        self.nodes[node] = Node {
+            bounds,
+            first: left,
+            count: 0,
+            axis,
+        };
+        self.split(left, start, start + mid, boxes);
+        self.split(left + 1, start + mid, end, boxes);
+    }
+
+    // Visita los objetos cuya caja cruza el rayo antes de la distancia más corta hasta
+    // ahora, de adelante hacia atrás. `visit` devuelve la distancia del impacto con ese
+    // objeto (infinito si no lo toca) o corta el recorrido con `Break`.
+    pub fn traverse(
+        &self,
+        ro: &Vec3,
+        rd: &Vec3,
+        mut t_max: f32,
+        mut visit: impl FnMut(usize) -> ControlFlow<(), f32>,
+    ) {
+        for &i in &self.unbounded {
+            match visit(i) {
+                ControlFlow::Continue(t) => t_max = t_max.min(t),
+                ControlFlow::Break(()) => return,
+            }
+        }
+        if self.nodes.is_empty() {
+            return;
+        }
+        let inv_rd = vec3(1.0 / rd.x, 1.0 / rd.y, 1.0 / rd.z);
+        let mut stack = [0usize; STACK_SIZE];
+        let mut top = 1;
+        while top > 0 {
+            top -= 1;
+            let node = &self.nodes[stack[top]];
+            if !node.bounds.hit(ro, &inv_rd, t_max) {
+                continue;
+            }
+            if node.count > 0 {
+                for &i in &self.order[node.first..node.first + node.count] {
+                    match visit(i) {
+                        ControlFlow::Continue(t) => t_max = t_max.min(t),
+                        ControlFlow::Break(()) => return,
+                    }
+                }
+                continue;
+            }
+            // El hijo del lado por donde entra el rayo va primero (queda arriba de la pila).
+            let (near, far) = if rd[node.axis] < 0.0 {
+                (node.first + 1, node.first)
+            } else {
+                (node.first, node.first + 1)
+            };
+            stack[top] = far;
+            stack[top + 1] = near;
+            top += 2;
+        }
+    }
+}
@@ -1,6 +1,93 @@
+use crate::cubemap::{self, CROSS_CELLS};
+#[cfg(feature = "raylib")]
+use crate::input::{Action, InputConfig};
 use glm::{Vec3, vec3};
 use nalgebra_glm as glm;
+use std::f32::consts::PI;
 
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Projection {
+    Perspective,
+    // Rayos paralelos; `ortho_height` es la altura visible en unidades de mundo.
+    Orthographic,
+    // Equidistante: el ángulo al eje crece lineal con el radio.
+    Fisheye,
+    // Panorama 360° equirectangular, nivelado con el horizonte.
+    Equirect,
+    // Cruz 4x3 con las 6 caras del cubo alineadas al mundo.
+    CubeCross,
+}
+
+impl Projection {
+    const ALL: [(Projection, &'static str); 5] = [
+        (Projection::Perspective, "perspectiva"),
+        (Projection::Orthographic, "orto"),
+        (Projection::Fisheye, "ojo_de_pez"),
+        (Projection::Equirect, "360"),
+        (Projection::CubeCross, "cubemap"),
+    ];
+
+    pub fn from_name(name: &str) -> Option<Projection> {
+        Self::ALL.iter().find(|(_, n)| *n == name).map(|(p, _)| *p)
+    }
+
+    pub fn name(self) -> &'static str {
+        Self::ALL
+            .iter()
+            .find(|(p, _)| *p == self)
+            .map_or("", |(_, n)| n)
+    }
+
+    pub fn next(self) -> Projection {
+        let i = Self::ALL.iter().position(|(p, _)| *p == self).unwrap_or(0);
+        Self::ALL[(i + 1) % Self::ALL.len()].0
+    }
+
+    // Tamaño natural de salida para un ancho dado (2:1 el panorama, 4:3 la cruz).
+    pub fn output_size(self, width: i32, height: i32) -> (i32, i32) {
+        match self {
+            Projection::Equirect => (width, width / 2),
+            Projection::CubeCross => (width, width * 3 / 4),
+            _ => (width, height),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum StereoLayout {
+    Off,
+    SideBySide,
+    OverUnder,
+    // Rojo del ojo izquierdo, verde/azul del derecho.
+    Anaglyph,
+}
+
+impl StereoLayout {
+    const ALL: [(StereoLayout, &'static str); 4] = [
+        (StereoLayout::Off, "no"),
+        (StereoLayout::SideBySide, "sbs"),
+        (StereoLayout::OverUnder, "ou"),
+        (StereoLayout::Anaglyph, "anaglifo"),
+    ];
+
+    pub fn from_name(name: &str) -> Option<StereoLayout> {
+        Self::ALL.iter().find(|(_, n)| *n == name).map(|(l, _)| *l)
+    }
+
+    pub fn name(self) -> &'static str {
+        Self::ALL
+            .iter()
+            .find(|(l, _)| *l == self)
+            .map_or("", |(_, n)| n)
+    }
+
+    pub fn next(self) -> StereoLayout {
+        let i = Self::ALL.iter().position(|(l, _)| *l == self).unwrap_or(0);
+        Self::ALL[(i + 1) % Self::ALL.len()].0
+    }
+}
+
+#[derive(Clone)]
 pub struct Camera {
     pub pos: Vec3,
     pub yaw: f32,
@@ -10,6 +97,19 @@ pub struct Camera {
     pub forward: Vec3,
     pub right: Vec3,
     pub up_cam: Vec3,
+    pub projection: Projection,
+    pub ortho_height: f32,
+    pub fisheye_fov_deg: f32,
+    pub stereo: StereoLayout,
+    // Distancia entre ojos y distancia al plano sin paralaje (unidades de mundo).
+    pub ipd: f32,
+    pub convergence: f32,
+    // Desplazamiento del ojo a lo largo de `right` (0 = cámara central).
+    eye_offset: f32,
+    // Pose (pos, yaw, pitch) al abrir el obturador; la actual es la del cierre.
+    pub shutter_open: Option<(Vec3, f32, f32)>,
+    // Desplazamiento subpíxel del rayo primario, en píxeles (antialias temporal).
+    pub jitter: (f32, f32),
 }
 
 impl Camera {
@@ -26,6 +126,15 @@ impl Camera {
             forward,
             right: vec3(1.0, 0.0, 0.0),
             up_cam: vec3(0.0, 1.0, 0.0),
+            projection: Projection::Perspective,
+            ortho_height: 8.0,
+            fisheye_fov_deg: 180.0,
+            stereo: StereoLayout::Off,
+            ipd: 0.065,
+            convergence: 3.0,
+            eye_offset: 0.0,
+            shutter_open: None,
+            jitter: (0.0, 0.0),
         };
         cam.rebuild_basis();
         cam
@@ -42,55 +151,220 @@ impl Camera {
     }
 
     pub fn ray_dir(&self, px: i32, py: i32, w: i32, h: i32) -> Vec3 {
-        let u = (((px as f32 + 0.5) / w as f32) * 2.0 - 1.0) * self.aspect * self.fov_tan;
-        let v = (1.0 - ((py as f32 + 0.5) / h as f32) * 2.0) * self.fov_tan;
+        // Frustum asimétrico: los dos ojos coinciden en el plano de convergencia.
+        let (jx, jy) = self.jitter;
+        let u = (((px as f32 + 0.5 + jx) / w as f32) * 2.0 - 1.0) * self.aspect * self.fov_tan
+            - self.eye_offset / self.convergence;
+        let v = (1.0 - ((py as f32 + 0.5 + jy) / h as f32) * 2.0) * self.fov_tan;
         glm::normalize(&(self.forward + self.right * u + self.up_cam * v))
     }
 
+    // Origen y dirección del rayo primario según la proyección; `None` fuera de la
+    // imagen útil (círculo del ojo de pez, huecos de la cruz).
+    pub fn primary_ray(&self, px: i32, py: i32, w: i32, h: i32) -> Option<(Vec3, Vec3)> {
+        let fx = (px as f32 + 0.5 + self.jitter.0) / w as f32;
+        let fy = (py as f32 + 0.5 + self.jitter.1) / h as f32;
+        match self.projection {
+            Projection::Perspective => Some((self.eye_pos(), self.ray_dir(px, py, w, h))),
+            Projection::Orthographic => {
+                let half_h = self.ortho_height * 0.5;
+                let u = (fx * 2.0 - 1.0) * self.aspect * half_h;
+                let v = (1.0 - fy * 2.0) * half_h;
+                Some((
+                    self.eye_pos() + self.right * u + self.up_cam * v,
+                    self.forward,
+                ))
+            }
+            Projection::Fisheye => {
+                let nx = (fx * 2.0 - 1.0) * w as f32 / h as f32;
+                let ny = 1.0 - fy * 2.0;
+                let r = (nx * nx + ny * ny).sqrt();
+                if r > 1.0 {
+                    return None;
+                }
+                let theta = r * (self.fisheye_fov_deg * 0.5).to_radians();
+                let phi = ny.atan2(nx);
+                let side = self.right * phi.cos() + self.up_cam * phi.sin();
+                Some((
+                    self.eye_pos(),
+                    glm::normalize(&(self.forward * theta.cos() + side * theta.sin())),
+                ))
+            }
+            Projection::Equirect => {
+                let lon = (fx - 0.5) * 2.0 * PI;
+                let lat = (0.5 - fy) * PI;
+                let fwd = vec3(self.yaw.cos(), 0.0, -self.yaw.sin());
+                let right = glm::cross(&fwd, &vec3(0.0, 1.0, 0.0));
+                let dir =
+                    (fwd * lon.cos() + right * lon.sin()) * lat.cos() + vec3(0.0, lat.sin(), 0.0);
+                // Estéreo omnidireccional (ODS): cada columna desplaza el ojo
+                // perpendicular a su propia dirección de vista.
+                let side = right * lon.cos() - fwd * lon.sin();
+                Some((self.pos + side * self.eye_offset, dir))
+            }
+            Projection::CubeCross => {
+                let cell = (w / 4).min(h / 3).max(1);
+                let (ox, oy) = ((w - cell * 4) / 2, (h - cell * 3) / 2);
+                let (cx, cy) = ((px - ox).div_euclid(cell), (py - oy).div_euclid(cell));
+                let face = CROSS_CELLS.iter().position(|&c| c == (cx, cy))?;
+                let s = ((px - ox - cx * cell) as f32 + 0.5) / cell as f32 * 2.0 - 1.0;
+                let t = ((py - oy - cy * cell) as f32 + 0.5) / cell as f32 * 2.0 - 1.0;
+                Some((
+                    self.eye_pos(),
+                    glm::normalize(&cubemap::face_to_dir(face, s, t)),
+                ))
+            }
+        }
+    }
+
+    // Inverso de `primary_ray` (sin jitter) para perspectiva y orto: coordenadas de píxel
+    // continuas de `p` (el centro del píxel i cae en i + 0.5) y su distancia medida como
+    // la del rayo primario.
+    pub fn project(&self, p: &Vec3, w: i32, h: i32) -> Option<(f32, f32, f32)> {
+        let d = p - self.eye_pos();
+        let z = glm::dot(&d, &self.forward);
+        let (u, v, dist) = match self.projection {
+            Projection::Perspective => {
+                if z <= 1e-4 {
+                    return None;
+                }
+                let u = glm::dot(&d, &self.right) / z + self.eye_offset / self.convergence;
+                let v = glm::dot(&d, &self.up_cam) / z;
+                (
+                    u / (self.aspect * self.fov_tan),
+                    v / self.fov_tan,
+                    glm::length(&d),
+                )
+            }
+            Projection::Orthographic => {
+                let half_h = self.ortho_height * 0.5;
+                let u = glm::dot(&d, &self.right) / (self.aspect * half_h);
+                (u, glm::dot(&d, &self.up_cam) / half_h, z)
+            }
+            _ => return None,
+        };
+        let px = (u + 1.0) * 0.5 * w as f32;
+        let py = (1.0 - v) * 0.5 * h as f32;
+        Some((px, py, dist))
+    }
+
+    fn eye_pos(&self) -> Vec3 {
+        self.pos + self.right * self.eye_offset
+    }
+
+    // Copia de la cámara para un ojo, con el aspecto de su mitad de imagen.
+    pub fn eye(&self, right_eye: bool, aspect: f32) -> Camera {
+        let mut eye = self.clone();
+        eye.eye_offset = if right_eye { 0.5 } else { -0.5 } * self.ipd;
+        eye.aspect = aspect;
+        eye
+    }
+
+    // Tamaño de salida según proyección y estéreo (sbs duplica el ancho, ou el alto).
+    pub fn output_size(&self, width: i32, height: i32) -> (i32, i32) {
+        let (w, h) = self.projection.output_size(width, height);
+        match self.stereo {
+            StereoLayout::SideBySide => (w * 2, h),
+            StereoLayout::OverUnder => (w, h * 2),
+            _ => (w, h),
+        }
+    }
+
+    // Cámara en el instante `time` del obturador (0 = abre, 1 = cierra).
+    pub fn at_time(&self, time: f32) -> Camera {
+        let mut cam = self.clone();
+        if let Some((pos, yaw, pitch)) = self.shutter_open {
+            let s = 1.0 - time;
+            cam.pos = self.pos + (pos - self.pos) * s;
+            cam.yaw = self.yaw + (yaw - self.yaw) * s;
+            cam.pitch = self.pitch + (pitch - self.pitch) * s;
+            cam.rebuild_basis();
+        }
+        cam
+    }
+
     pub fn set_aspect(&mut self, aspect: f32) {
         self.aspect = aspect;
     }
 
-    pub fn update_from_input(&mut self, rl: &raylib::RaylibHandle) {
-        use raylib::consts::KeyboardKey::*;
-        let rot = 1.0_f32.to_radians();
-        if rl.is_key_down(KEY_RIGHT) {
+    pub fn fov_deg(&self) -> f32 {
+        (self.fov_tan.atan() * 2.0).to_degrees()
+    }
+
+    // Pose completa (la usan los recorridos de cámara).
+    pub fn set_pose(&mut self, pos: Vec3, yaw: f32, pitch: f32, fov_deg: f32) {
+        self.pos = pos;
+        self.yaw = yaw;
+        self.pitch = pitch;
+        self.fov_tan = (fov_deg.to_radians() * 0.5).tan();
+        self.rebuild_basis();
+    }
+}
+
+// Control con teclado, mouse y gamepad (solo el visor).
+#[cfg(feature = "raylib")]
+impl Camera {
+    // Vuelo libre: mira y se mueve a lo largo de la vista (Q/E en el eje de la cámara).
+    pub fn update_from_input(&mut self, rl: &mut raylib::RaylibHandle, input: &InputConfig) {
+        self.look_from_input(rl, input);
+        let m = self.move_input(rl, input);
+        self.pos += self.forward * m.z + self.right * m.x + self.up_cam * m.y;
+    }
+
+    pub fn look_from_input(&mut self, rl: &mut raylib::RaylibHandle, input: &InputConfig) {
+        let dt = rl.get_frame_time();
+
+        if input.pressed(rl, Action::ToggleMouse) {
+            if rl.is_cursor_hidden() {
+                rl.enable_cursor();
+            } else {
+                rl.disable_cursor();
+            }
+        }
+
+        let rot = (input.turn_speed_deg * dt).to_radians();
+        if input.down(rl, Action::YawRight) {
             self.yaw -= rot;
         }
-        if rl.is_key_down(KEY_LEFT) {
+        if input.down(rl, Action::YawLeft) {
             self.yaw += rot;
         }
-        if rl.is_key_down(KEY_UP) {
+        if input.down(rl, Action::PitchUp) {
             self.pitch += rot;
         }
-        if rl.is_key_down(KEY_DOWN) {
+        if input.down(rl, Action::PitchDown) {
             self.pitch -= rot;
         }
+        if rl.is_cursor_hidden() {
+            let md = rl.get_mouse_delta();
+            let sens = input.mouse_sensitivity.to_radians();
+            let dy = if input.invert_y { md.y } else { -md.y };
+            self.yaw -= md.x * sens;
+            self.pitch += dy * sens;
+        }
+        let pad = input.gamepad_state(rl);
+        let look = (input.gamepad_look_speed_deg * dt).to_radians();
+        self.yaw -= pad.look_x * look;
+        self.pitch += pad.look_y * look;
         self.pitch = self.pitch.clamp(-1.3, 1.3);
         self.rebuild_basis();
+    }
 
-        let base = if rl.is_key_down(KEY_LEFT_SHIFT) {
-            0.20
+    // Desplazamiento pedido este cuadro en ejes locales (x derecha, y arriba, z adelante).
+    pub fn move_input(&self, rl: &raylib::RaylibHandle, input: &InputConfig) -> Vec3 {
+        let base = if input.down(rl, Action::Fast) {
+            input.move_speed * input.fast_multiplier
         } else {
-            0.08
+            input.move_speed
+        } * rl.get_frame_time();
+        let axis = |neg: Action, pos: Action| {
+            input.down(rl, pos) as i32 as f32 - input.down(rl, neg) as i32 as f32
         };
-        if rl.is_key_down(KEY_W) {
-            self.pos += self.forward * base;
-        }
-        if rl.is_key_down(KEY_S) {
-            self.pos -= self.forward * base;
-        }
-        if rl.is_key_down(KEY_A) {
-            self.pos -= self.right * base;
-        }
-        if rl.is_key_down(KEY_D) {
-            self.pos += self.right * base;
-        }
-        if rl.is_key_down(KEY_Q) {
-            self.pos -= self.up_cam * base;
-        }
-        if rl.is_key_down(KEY_E) {
-            self.pos += self.up_cam * base;
-        }
+        let pad = input.gamepad_state(rl);
+        vec3(
+            axis(Action::Left, Action::Right) + pad.strafe,
+            axis(Action::Down, Action::Up) + pad.vertical,
+            axis(Action::Back, Action::Forward) + pad.forward,
+        ) * base
     }
 }
@@ -0,0 +1,200 @@
+use crate::camera::Camera;
+use crate::error::Error;
+use glm::{Vec3, vec3};
+use nalgebra_glm as glm;
+use std::f32::consts::PI;
+
+#[derive(Clone, Copy, Debug)]
+pub struct Keyframe {
+    pub time: f32,
+    pub pos: Vec3,
+    pub yaw: f32,
+    pub pitch: f32,
+    pub fov_deg: f32,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Easing {
+    Linear,
+    // Arranca y frena suave en los extremos del recorrido.
+    Smooth,
+}
+
+impl Easing {
+    fn apply(self, t: f32) -> f32 {
+        match self {
+            Easing::Linear => t,
+            Easing::Smooth => t * t * (3.0 - 2.0 * t),
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Easing::Linear => "lineal",
+            Easing::Smooth => "suave",
+        }
+    }
+}
+
+// Recorrido de cámara por keyframes, interpolado con Catmull-Rom.
+#[derive(Clone, Debug)]
+pub struct CameraPath {
+    pub keys: Vec<Keyframe>,
+    pub easing: Easing,
+}
+
+impl Default for CameraPath {
+    fn default() -> Self {
+        Self {
+            keys: vec![],
+            easing: Easing::Smooth,
+        }
+    }
+}
+
+fn catmull_rom(p0: f32, p1: f32, p2: f32, p3: f32, t: f32) -> f32 {
+    let (t2, t3) = (t * t, t * t * t);
+    0.5 * (2.0 * p1
+        + (p2 - p0) * t
+        + (2.0 * p0 - 5.0 * p1 + 4.0 * p2 - p3) * t2
+        + (3.0 * p1 - p0 - 3.0 * p2 + p3) * t3)
+}
+
+impl CameraPath {
+    pub fn duration(&self) -> f32 {
+        self.keys.last().map_or(0.0, |k| k.time)
+    }
+
+    // Añade la pose actual `dt` segundos después del último keyframe.
+    pub fn push_camera(&mut self, cam: &Camera, dt: f32) {
+        let time = if self.keys.is_empty() {
+            0.0
+        } else {
+            self.duration() + dt
+        };
+        let mut yaw = cam.yaw;
+        // El yaw se desenrolla respecto al anterior para no dar la vuelta larga.
+        if let Some(prev) = self.keys.last() {
+            yaw = prev.yaw + (yaw - prev.yaw + PI).rem_euclid(2.0 * PI) - PI;
+        }
+        self.keys.push(Keyframe {
+            time,
+            pos: cam.pos,
+            yaw,
+            pitch: cam.pitch,
+            fov_deg: cam.fov_deg(),
+        });
+    }
+
+    pub fn pop(&mut self) {
+        self.keys.pop();
+    }
+
+    pub fn sample(&self, time: f32) -> Option<Keyframe> {
+        let n = self.keys.len();
+        if n == 0 {
+            return None;
+        }
+        let dur = self.duration();
+        if n == 1 || dur <= 0.0 {
+            return Some(self.keys[0]);
+        }
+        let time = self.easing.apply((time / dur).clamp(0.0, 1.0)) * dur;
+        let i = self
+            .keys
+            .windows(2)
+            .position(|w| time <= w[1].time)
+            .unwrap_or(n - 2);
+        let (k1, k2) = (self.keys[i], self.keys[i + 1]);
+        let k0 = self.keys[i.saturating_sub(1)];
+        let k3 = self.keys[(i + 2).min(n - 1)];
+        let span = (k2.time - k1.time).max(1e-6);
+        let t = ((time - k1.time) / span).clamp(0.0, 1.0);
+        let cr = |f: fn(&Keyframe) -> f32| catmull_rom(f(&k0), f(&k1), f(&k2), f(&k3), t);
+        Some(Keyframe {
+            time,
+            pos: vec3(cr(|k| k.pos.x), cr(|k| k.pos.y), cr(|k| k.pos.z)),
+            yaw: cr(|k| k.yaw),
+            pitch: cr(|k| k.pitch),
+            fov_deg: cr(|k| k.fov_deg),
+        })
+    }
+
+    pub fn apply(&self, cam: &mut Camera, time: f32) {
+        if let Some(k) = self.sample(time) {
+            cam.set_pose(k.pos, k.yaw, k.pitch, k.fov_deg);
+        }
+    }
+
+    // Formato de texto: `suavizado = lineal|suave` y una línea `key t x y z yaw pitch fov`
+    // por keyframe (ángulos en grados); `#` inicia un comentario.
+    pub fn save(&self, path: &str) -> Result<(), Error> {
+        let mut text = String::from("# t x y z yaw pitch fov\n");
+        text += &format!("suavizado = {}\n", self.easing.name());
+        for k in &self.keys {
+            text += &format!(
+                "key {:.3} {:.4} {:.4} {:.4} {:.3} {:.3} {:.2}\n",
+                k.time,
+                k.pos.x,
+                k.pos.y,
+                k.pos.z,
+                k.yaw.to_degrees(),
+                k.pitch.to_degrees(),
+                k.fov_deg
+            );
+        }
+        std::fs::write(path, text).map_err(|e| Error::write(path, e))
+    }
+
+    pub fn load(path: &str) -> Result<Self, Error> {
+        let text = std::fs::read_to_string(path).map_err(|e| Error::read(path, e))?;
+        Self::parse(&text).map_err(|e| Error::scene(path, e))
+    }
+
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let mut path = Self::default();
+        for (i, raw) in text.lines().enumerate() {
+            let line = raw.split('#').next().unwrap_or("").trim();
+            if line.is_empty() {
+                continue;
+            }
+            if let Some((key, value)) = line.split_once('=') {
+                if key.trim() != "suavizado" {
+                    return Err(format!(
+                        "línea {}: clave desconocida '{}'",
+                        i + 1,
+                        key.trim()
+                    ));
+                }
+                path.easing = match value.trim() {
+                    "lineal" => Easing::Linear,
+                    "suave" => Easing::Smooth,
+                    v => return Err(format!("línea {}: suavizado desconocido '{v}'", i + 1)),
+                };
+                continue;
+            }
+            let mut parts = line.split_whitespace();
+            if parts.next() != Some("key") {
+                return Err(format!("línea {}: se esperaba 'key'", i + 1));
+            }
+            let v = parts
+                .map(|p| p.parse::<f32>())
+                .collect::<Result<Vec<_>, _>>()
+                .map_err(|_| format!("línea {}: número inválido", i + 1))?;
+            if v.len() != 7 {
+                return Err(format!("línea {}: se esperaban 7 valores", i + 1));
+            }
+            if path.keys.last().is_some_and(|k| v[0] < k.time) {
+                return Err(format!("línea {}: los tiempos deben ir en orden", i + 1));
+            }
+            path.keys.push(Keyframe {
+                time: v[0],
+                pos: vec3(v[1], v[2], v[3]),
+                yaw: v[4].to_radians(),
+                pitch: v[5].to_radians(),
+                fov_deg: v[6],
+            });
+        }
+        Ok(path)
+    }
+}
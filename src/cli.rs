@@ -0,0 +1,116 @@
+// Opciones de línea de comandos que comparten el visor y el render offline.
+use proyect2_raytracing::camera::{Camera, Projection, StereoLayout};
+use proyect2_raytracing::color::Material;
+use proyect2_raytracing::denoise::Denoiser;
+use proyect2_raytracing::editor;
+use proyect2_raytracing::objects::Object;
+use proyect2_raytracing::renderer::{RenderMode, RenderSettings};
+use proyect2_raytracing::scene::Fog;
+
+// Busca `--nombre=valor` o `--nombre valor` en los argumentos.
+pub fn arg_value(name: &str) -> Option<String> {
+    let args: Vec<String> = std::env::args().collect();
+    for (i, a) in args.iter().enumerate() {
+        if let Some(v) = a.strip_prefix(name).and_then(|r| r.strip_prefix('=')) {
+            return Some(v.to_string());
+        }
+        if a == name {
+            return args.get(i + 1).cloned();
+        }
+    }
+    None
+}
+
+pub fn camera_options(cam: &mut Camera) {
+    // `--proyeccion perspectiva|orto|ojo_de_pez|360|cubemap` (V la cambia en vivo).
+    if let Some(name) = arg_value("--proyeccion") {
+        match Projection::from_name(&name) {
+            Some(p) => cam.projection = p,
+            None => eprintln!("Proyección desconocida '{name}'; uso perspectiva"),
+        }
+    }
+    // `--estereo sbs|ou|anaglifo` (B lo cambia en vivo); con `--proyeccion 360` da ODS.
+    if let Some(name) = arg_value("--estereo") {
+        match StereoLayout::from_name(&name) {
+            Some(l) => cam.stereo = l,
+            None => eprintln!("Modo estéreo desconocido '{name}'; lo desactivo"),
+        }
+    }
+    if let Some(v) = arg_value("--ipd").and_then(|v| v.parse().ok()) {
+        cam.ipd = v;
+    }
+    if let Some(v) = arg_value("--convergencia").and_then(|v| v.parse::<f32>().ok()) {
+        cam.convergence = v.max(0.01);
+    }
+}
+
+// `--modo normales|uv|profundidad|material|objeto|rebotes|sombras|costo|ao` (teclas 1..0).
+// `--ibl si|no`: luz ambiente del cielo (I en vivo); apagada por defecto.
+pub fn render_options(settings: &mut RenderSettings) {
+    if let Some(name) = arg_value("--modo") {
+        match RenderMode::from_name(&name) {
+            Some(m) => settings.mode = m,
+            None => eprintln!("Modo de render desconocido '{name}'; uso sombreado"),
+        }
+    }
+    match arg_value("--ibl").as_deref() {
+        None => {}
+        Some("si") => settings.ibl = true,
+        Some("no") => settings.ibl = false,
+        Some(other) => eprintln!("Opción de IBL desconocida '{other}'; la dejo apagada"),
+    }
+}
+
+// `--denoise n` activa el denoiser con n pasadas; `--denoise-color s` fija la
+// tolerancia de color.
+pub fn denoiser_options(denoiser: &mut Denoiser) {
+    if let Some(n) = arg_value("--denoise").and_then(|v| v.parse().ok()) {
+        denoiser.enabled = true;
+        denoiser.iterations = n;
+    }
+    if let Some(v) = arg_value("--denoise-color").and_then(|v| v.parse::<f32>().ok()) {
+        denoiser.sigma_color = v.max(0.01);
+    }
+}
+
+// `--niebla densidad` (por unidad de distancia; 0 la apaga).
+pub fn fog_options(fog: &mut Fog) {
+    if let Some(v) = arg_value("--niebla").and_then(|v| v.parse::<f32>().ok()) {
+        fog.density = v.max(0.0);
+    }
+}
+
+// `--textura-faltante cuadros|error`: una textura que no carga se reemplaza por un
+// damero magenta (por defecto) o corta el programa con el error.
+pub fn strict_textures() -> bool {
+    match arg_value("--textura-faltante").as_deref() {
+        None | Some("cuadros") => false,
+        Some("error") => true,
+        Some(other) => {
+            eprintln!("Opción de textura faltante desconocida '{other}'; uso cuadros");
+            false
+        }
+    }
+}
+
+// `--escena archivo` reemplaza el diorama por uno guardado con el editor (Ctrl+S);
+// por defecto `escena.txt` si existe. Devuelve la ruta (para volver a guardar) y los objetos;
+// los materiales guardados se aplican sobre `palette`.
+pub fn scene(palette: &mut [Material], diorama: Vec<Object>) -> (String, Vec<Object>) {
+    let scene_file = arg_value("--escena").unwrap_or_else(|| "escena.txt".to_string());
+    if !std::path::Path::new(&scene_file).exists() {
+        return (scene_file, diorama);
+    }
+    match editor::load_scene(&scene_file, palette) {
+        Ok(loaded) => (scene_file, loaded),
+        Err(e) => {
+            eprintln!("No pude leer la escena ({e}); uso el diorama");
+            (scene_file, diorama)
+        }
+    }
+}
+
+// `--sky` (por defecto `assets/skybox.png`), ver `diorama::sky`.
+pub fn sky_path() -> String {
+    arg_value("--sky").unwrap_or_else(|| "assets/skybox.png".to_string())
+}
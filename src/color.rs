@@ -1,7 +1,7 @@
-use raylib::prelude::Color;
+use nalgebra_glm as glm;
 use std::sync::Arc;
 
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub struct Rgb {
     pub r: u8,
     pub g: u8,
@@ -11,22 +11,33 @@ impl Rgb {
     pub fn new(r: u8, g: u8, b: u8) -> Self {
         Self { r, g, b }
     }
-    pub fn to_raylib(self) -> Color {
-        Color::new(self.r, self.g, self.b, 255)
+    #[cfg(feature = "raylib")]
+    pub fn to_raylib(self) -> raylib::prelude::Color {
+        raylib::prelude::Color::new(self.r, self.g, self.b, 255)
     }
 
     pub fn scale(self, s: f32) -> Self {
         let f = |x: u8| ((x as f32 * s).clamp(0.0, 255.0)) as u8;
         Rgb::new(f(self.r), f(self.g), f(self.b))
     }
+    // Suma y producto saturados, como funciones asociadas (`Rgb::add(a, b)`).
+    #[allow(clippy::should_implement_trait)]
     pub fn add(a: Rgb, b: Rgb) -> Rgb {
         let f = |x: u8, y: u8| (x as i32 + y as i32).clamp(0, 255) as u8;
         Rgb::new(f(a.r, b.r), f(a.g, b.g), f(a.b, b.b))
     }
+    #[allow(clippy::should_implement_trait)]
     pub fn mul(a: Rgb, b: Rgb) -> Rgb {
         let f = |x: u8, y: u8| ((x as u16 * y as u16) / 255) as u8;
         Rgb::new(f(a.r, b.r), f(a.g, b.g), f(a.b, b.b))
     }
+    pub fn to_vec3(self) -> glm::Vec3 {
+        glm::vec3(self.r as f32, self.g as f32, self.b as f32) / 255.0
+    }
+    pub fn from_vec3(v: &glm::Vec3) -> Rgb {
+        let f = |x: f32| (x * 255.0).round().clamp(0.0, 255.0) as u8;
+        Rgb::new(f(v.x), f(v.y), f(v.z))
+    }
     pub fn lerp(a: Rgb, b: Rgb, t: f32) -> Rgb {
         let f =
             |x: u8, y: u8| ((x as f32 * (1.0 - t) + y as f32 * t).round()).clamp(0.0, 255.0) as u8;
@@ -42,6 +53,8 @@ pub enum TexSlot {
 
 #[derive(Debug, Clone)]
 pub struct Material {
+    // Nombre de la paleta; identifica el material en los archivos de escena.
+    pub name: &'static str,
     pub tex: TexSlot,           
     pub albedo_color: Rgb,      
     pub specular: f32,          
@@ -50,11 +63,14 @@ pub struct Material {
     pub ior: f32,               
     pub emission: Rgb,          
     pub emission_strength: f32,
+    // Amplitud de las ondas que perturban la normal (agua); 0 = superficie lisa.
+    pub ripple: f32,
 }
 
 impl Material {
     pub fn solid(c: Rgb) -> Self {
         Self {
+            name: "solido",
             tex: TexSlot::None,
             albedo_color: c,
             specular: 0.08,
@@ -63,10 +79,12 @@ impl Material {
             ior: 1.0,
             emission: Rgb::new(0, 0, 0),
             emission_strength: 0.0,
+            ripple: 0.0,
         }
     }
     pub fn solid_with(c: Rgb, specular: f32, reflectivity: f32) -> Self {
         Self {
+            name: "solido_brillo",
             tex: TexSlot::None,
             albedo_color: c,
             specular,
@@ -75,12 +93,14 @@ impl Material {
             ior: 1.0,
             emission: Rgb::new(0, 0, 0),
             emission_strength: 0.0,
+            ripple: 0.0,
         }
     }
-    pub fn sample_albedo(&self, uv: Option<(f32, f32)>) -> Rgb {
+    // `time` en segundos de escena elige el cuadro de las texturas animadas.
+    pub fn sample_albedo(&self, uv: Option<(f32, f32)>, time: f32) -> Rgb {
         match (&self.tex, uv) {
             (TexSlot::Some(tex), Some((u, v))) => {
-                let t = tex.sample_repeat(u, v);
+                let t = tex.sample_at(u, v, time);
                 Rgb::mul(t, self.albedo_color)
             }
             _ => self.albedo_color,
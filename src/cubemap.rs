@@ -0,0 +1,175 @@
+use crate::error::Error;
+use crate::texture::HdrTexture;
+use glm::{Vec3, vec3};
+use nalgebra_glm as glm;
+use std::path::Path;
+
+// Orden de caras (convención OpenGL): +X, -X, +Y, -Y, +Z, -Z.
+const FACE_NAMES: [[&str; 6]; 3] = [
+    ["px", "nx", "py", "ny", "pz", "nz"],
+    ["posx", "negx", "posy", "negy", "posz", "negz"],
+    ["right", "left", "top", "bottom", "front", "back"],
+];
+const FACE_EXTS: [&str; 3] = ["hdr", "exr", "png"];
+// (columna, fila) de cada cara en la cruz horizontal 4x3.
+pub const CROSS_CELLS: [(i32, i32); 6] = [(2, 1), (0, 1), (1, 0), (1, 2), (1, 1), (3, 1)];
+
+pub struct CubeMap {
+    pub size: i32,
+    faces: Vec<HdrTexture>,
+}
+
+// Cara y coordenadas (s, t) en [-1, 1] de una dirección; t crece hacia abajo.
+fn dir_to_face(d: &Vec3) -> (usize, f32, f32) {
+    let (ax, ay, az) = (d.x.abs(), d.y.abs(), d.z.abs());
+    if ax >= ay && ax >= az {
+        if d.x > 0.0 {
+            (0, -d.z / ax, -d.y / ax)
+        } else {
+            (1, d.z / ax, -d.y / ax)
+        }
+    } else if ay >= az {
+        if d.y > 0.0 {
+            (2, d.x / ay, d.z / ay)
+        } else {
+            (3, d.x / ay, -d.z / ay)
+        }
+    } else if d.z > 0.0 {
+        (4, d.x / az, -d.y / az)
+    } else {
+        (5, -d.x / az, -d.y / az)
+    }
+}
+
+pub fn face_to_dir(face: usize, s: f32, t: f32) -> Vec3 {
+    match face {
+        0 => vec3(1.0, -t, -s),
+        1 => vec3(-1.0, -t, s),
+        2 => vec3(s, 1.0, t),
+        3 => vec3(s, -1.0, -t),
+        4 => vec3(s, -t, 1.0),
+        _ => vec3(-s, -t, -1.0),
+    }
+}
+
+fn crop(img: &HdrTexture, x0: i32, y0: i32, size: i32, rotate_180: bool) -> HdrTexture {
+    let mut pixels = Vec::with_capacity((size * size) as usize);
+    for y in 0..size {
+        for x in 0..size {
+            let (sx, sy) = if rotate_180 {
+                (size - 1 - x, size - 1 - y)
+            } else {
+                (x, y)
+            };
+            pixels.push(img.pixels[((y0 + sy) * img.w + x0 + sx) as usize]);
+        }
+    }
+    HdrTexture {
+        w: size,
+        h: size,
+        pixels,
+    }
+}
+
+impl CubeMap {
+    pub fn from_faces(faces: Vec<HdrTexture>) -> Result<Self, String> {
+        if faces.len() != 6 {
+            return Err(format!("un cubemap necesita 6 caras, hay {}", faces.len()));
+        }
+        let size = faces[0].w;
+        if faces.iter().any(|f| f.w != size || f.h != size) {
+            return Err("las caras del cubemap deben ser cuadradas y del mismo tamaño".into());
+        }
+        Ok(Self { size, faces })
+    }
+
+    pub fn from_face_files(paths: &[&str; 6]) -> Result<Self, Error> {
+        let faces = paths
+            .iter()
+            .map(|p| HdrTexture::from_file(p))
+            .collect::<Result<Vec<_>, _>>()?;
+        Self::from_faces(faces).map_err(|e| Error::format(paths[0], e))
+    }
+
+    // Carpeta con px/nx/py/ny/pz/nz (o posx..., o right/left/top/bottom/front/back).
+    pub fn from_dir(dir: &str) -> Result<Self, Error> {
+        for names in FACE_NAMES.iter() {
+            let found: Vec<String> = names
+                .iter()
+                .filter_map(|n| {
+                    FACE_EXTS
+                        .iter()
+                        .map(|e| Path::new(dir).join(format!("{n}.{e}")))
+                        .find(|p| p.is_file())
+                        .map(|p| p.to_string_lossy().into_owned())
+                })
+                .collect();
+            if found.len() == 6 {
+                let paths: [&str; 6] = std::array::from_fn(|i| found[i].as_str());
+                return Self::from_face_files(&paths);
+            }
+        }
+        Err(Error::format(dir, "no encontré las 6 caras del cubemap"))
+    }
+
+    // Cruz horizontal (4x3) o vertical (3x4) en una sola imagen.
+    pub fn from_cross(img: &HdrTexture) -> Result<Self, String> {
+        // (columna, fila, rotada 180°) de cada cara en la cuadrícula.
+        let cells: [(i32, i32, bool); 6] = if img.w * 3 == img.h * 4 {
+            CROSS_CELLS.map(|(cx, cy)| (cx, cy, false))
+        } else if img.w * 4 == img.h * 3 {
+            [
+                (2, 1, false),
+                (0, 1, false),
+                (1, 0, false),
+                (1, 2, false),
+                (1, 1, false),
+                (1, 3, true),
+            ]
+        } else {
+            return Err(format!(
+                "{}x{} no es una cruz de cubemap (4:3 o 3:4)",
+                img.w, img.h
+            ));
+        };
+        let size = img.w.max(img.h) / 4;
+        let faces = cells
+            .iter()
+            .map(|&(cx, cy, rot)| crop(img, cx * size, cy * size, size, rot))
+            .collect();
+        Self::from_faces(faces)
+    }
+
+    pub fn is_cross_layout(w: i32, h: i32) -> bool {
+        w * 3 == h * 4 || w * 4 == h * 3
+    }
+
+    fn texel(&self, face: usize, x: i32, y: i32) -> Vec3 {
+        let n = self.size;
+        if (0..n).contains(&x) && (0..n).contains(&y) {
+            return self.faces[face].pixels[(y * n + x) as usize];
+        }
+        // Fuera de la cara: se extrapola la dirección del texel y se busca en la vecina.
+        let s = (x as f32 + 0.5) / n as f32 * 2.0 - 1.0;
+        let t = (y as f32 + 0.5) / n as f32 * 2.0 - 1.0;
+        let (f2, s2, t2) = dir_to_face(&face_to_dir(face, s, t));
+        let x2 = (((s2 + 1.0) * 0.5 * n as f32) as i32).clamp(0, n - 1);
+        let y2 = (((t2 + 1.0) * 0.5 * n as f32) as i32).clamp(0, n - 1);
+        self.faces[f2].pixels[(y2 * n + x2) as usize]
+    }
+
+    // Bilineal; en los bordes toma los texeles de la cara adyacente.
+    pub fn sample(&self, dir: &Vec3) -> Vec3 {
+        let (face, s, t) = dir_to_face(&glm::normalize(dir));
+        let n = self.size as f32;
+        let fx = (s + 1.0) * 0.5 * n - 0.5;
+        let fy = (t + 1.0) * 0.5 * n - 0.5;
+        let (x0, y0) = (fx.floor(), fy.floor());
+        let (tx, ty) = (fx - x0, fy - y0);
+        let (x0, y0) = (x0 as i32, y0 as i32);
+        let top = self.texel(face, x0, y0) * (1.0 - tx) + self.texel(face, x0 + 1, y0) * tx;
+        let bottom =
+            self.texel(face, x0, y0 + 1) * (1.0 - tx) + self.texel(face, x0 + 1, y0 + 1) * tx;
+        top * (1.0 - ty) + bottom * ty
+    }
+}
@@ -0,0 +1,124 @@
+use crate::aov::AovBuffer;
+use crate::color::Rgb;
+use crate::framebuffer::FrameBuffer;
+use glm::{Vec3, vec3};
+use nalgebra_glm as glm;
+
+// Núcleo B3-spline de 5 taps del filtro à-trous.
+const KERNEL: [f32; 5] = [1.0 / 16.0, 1.0 / 4.0, 3.0 / 8.0, 1.0 / 4.0, 1.0 / 16.0];
+// Albedo mínimo al demodular, para no dividir por casi cero en superficies negras.
+const MIN_ALBEDO: f32 = 0.02;
+
+// Filtro à-trous con bordes guiados por albedo, normal y profundidad (Dammertz et al. 2010).
+// Se filtra la iluminación (color / albedo) para no borronear las texturas.
+#[derive(Clone, Copy, Debug)]
+pub struct Denoiser {
+    pub enabled: bool,
+    // Pasadas; el paso entre taps se duplica en cada una (1, 2, 4, ...).
+    pub iterations: u32,
+    // Tolerancias: cuanto más grandes, más se mezcla a través de esa diferencia.
+    pub sigma_color: f32,
+    pub sigma_normal: f32,
+    pub sigma_depth: f32,
+    pub sigma_albedo: f32,
+}
+
+impl Default for Denoiser {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            iterations: 3,
+            sigma_color: 0.5,
+            sigma_normal: 0.1,
+            sigma_depth: 0.05,
+            sigma_albedo: 0.1,
+        }
+    }
+}
+
+impl Denoiser {
+    // `features` debe venir de `renderer::render_features` con el mismo tamaño que `fb`.
+    pub fn apply(&self, fb: &mut FrameBuffer, features: &AovBuffer) {
+        let (w, h) = (fb.width, fb.height);
+        if self.iterations == 0 || features.width != w || features.height != h {
+            return;
+        }
+        let data = &fb.pixels;
+        if data.len() < (w * h) as usize {
+            return;
+        }
+        let f = &features.pixels;
+        let albedo = |i: usize| {
+            if f[i].depth.is_finite() {
+                glm::max(&f[i].albedo, MIN_ALBEDO)
+            } else {
+                vec3(1.0, 1.0, 1.0)
+            }
+        };
+        let mut light: Vec<Vec3> = data
+            .iter()
+            .enumerate()
+            .map(|(i, c)| {
+                let col = vec3(c.r as f32, c.g as f32, c.b as f32) / 255.0;
+                col.component_div(&albedo(i))
+            })
+            .collect();
+
+        let mut next = light.clone();
+        for it in 0..self.iterations {
+            let step = 1 << it;
+            // La tolerancia de color se achica con cada pasada, como en el paper.
+            let sc = self.sigma_color * self.sigma_color / (1 << it) as f32;
+            for y in 0..h {
+                for x in 0..w {
+                    let p = (y * w + x) as usize;
+                    let (cp, fp) = (light[p], &f[p]);
+                    let mut sum = vec3(0.0, 0.0, 0.0);
+                    let mut wsum = 0.0;
+                    for (ky, hy) in KERNEL.iter().enumerate() {
+                        let qy = y + (ky as i32 - 2) * step;
+                        if !(0..h).contains(&qy) {
+                            continue;
+                        }
+                        for (kx, hx) in KERNEL.iter().enumerate() {
+                            let qx = x + (kx as i32 - 2) * step;
+                            if !(0..w).contains(&qx) {
+                                continue;
+                            }
+                            let q = (qy * w + qx) as usize;
+                            let fq = &f[q];
+                            if fp.depth.is_finite() != fq.depth.is_finite() {
+                                continue;
+                            }
+                            let dc = light[q] - cp;
+                            let mut wgt = hx * hy * (-glm::dot(&dc, &dc) / sc.max(1e-6)).exp();
+                            if fp.depth.is_finite() {
+                                let dn = (1.0 - glm::dot(&fp.normal, &fq.normal)).max(0.0);
+                                let dz = (fp.depth - fq.depth).abs()
+                                    / (self.sigma_depth * step as f32 * fp.depth).max(1e-6);
+                                let da = fp.albedo - fq.albedo;
+                                wgt *= (-dn / self.sigma_normal.max(1e-6)).exp()
+                                    * (-dz).exp()
+                                    * (-glm::dot(&da, &da)
+                                        / (self.sigma_albedo * self.sigma_albedo).max(1e-6))
+                                    .exp();
+                            }
+                            sum += light[q] * wgt;
+                            wsum += wgt;
+                        }
+                    }
+                    next[p] = if wsum > 0.0 { sum / wsum } else { cp };
+                }
+            }
+            std::mem::swap(&mut light, &mut next);
+        }
+
+        for y in 0..h {
+            for x in 0..w {
+                let i = (y * w + x) as usize;
+                let c = light[i].component_mul(&albedo(i));
+                fb.set_pixel_color(x, y, Rgb::from_vec3(&c));
+            }
+        }
+    }
+}
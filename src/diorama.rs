@@ -0,0 +1,471 @@
+// El diorama de la casa de ladrillo (plataforma grande, árbol y charco). Lo comparten
+// el visor y el render offline.
+use proyect2_raytracing::animation::{Channel, Curve, Timeline, Track};
+use proyect2_raytracing::camera::Camera;
+use proyect2_raytracing::color::{Material, Rgb, TexSlot};
+use proyect2_raytracing::editor;
+use proyect2_raytracing::environment::{EnvSource, Environment};
+use proyect2_raytracing::error::Error;
+use proyect2_raytracing::objects::{Cube, Object};
+use proyect2_raytracing::renderer::PointLight;
+use proyect2_raytracing::sky::PhysicalSky;
+use proyect2_raytracing::texture::Texture;
+use glm::vec3;
+use nalgebra_glm as glm;
+use std::sync::Arc;
+
+// Vista lateral inicial.
+pub fn camera(aspect: f32) -> Camera {
+    Camera::new(
+        vec3(0.0, 1.8, 2.8),  // pos
+        vec3(0.0, 3.6, -4.8), // target
+        vec3(0.0, 1.0, 10.0), // up
+        60.0,
+        aspect,
+    )
+}
+
+// Una textura que no carga se reemplaza por un damero magenta, salvo con `strict`.
+fn or_missing(loaded: Result<Texture, Error>, strict: bool) -> Result<Arc<Texture>, Error> {
+    match loaded {
+        Ok(tex) => Ok(Arc::new(tex)),
+        Err(e) if !strict => {
+            eprintln!("No pude cargar la textura ({e}); uso el damero");
+            Ok(Arc::new(Texture::missing()))
+        }
+        Err(e) => Err(e),
+    }
+}
+
+// Materiales del diorama. Es también la paleta del editor de bloques: el nombre de cada
+// material se usa en el archivo de escena.
+pub fn palette(strict_textures: bool) -> Result<Vec<Material>, Error> {
+    let load = |path: &str| or_missing(Texture::from_file(path), strict_textures);
+    let tex_brick = load("assets/texture.png")?;
+    let tex_ground = load("assets/ground.png")?;
+    let tex_glowstone = load("assets/glowstone.png")?;
+    let tex_bark = load("assets/bark.png")?;
+    let tex_leaves = load("assets/leaves.png")?;
+    // Opcional: tira animada estilo Minecraft para el agua; sin .mcmeta va a 8 cuadros/s.
+    let water_path = "assets/water_still.png";
+    let tex_water = if !std::path::Path::new(water_path).exists() {
+        None
+    } else if std::path::Path::new(&format!("{water_path}.mcmeta")).exists() {
+        Some(load(water_path)?)
+    } else {
+        Some(or_missing(
+            Texture::from_strip(water_path, 8.0),
+            strict_textures,
+        )?)
+    };
+
+    let mat_brick = Material {
+        name: "ladrillo",
+        tex: TexSlot::Some(tex_brick),
+        albedo_color: Rgb::new(200, 170, 120),
+        specular: 0.12,
+        reflectivity: 0.08,
+        transparency: 0.0,
+        ior: 1.0,
+        emission: Rgb::new(0, 0, 0),
+        emission_strength: 0.0,
+        ripple: 0.0,
+    };
+    let mat_ground = Material {
+        name: "tierra",
+        tex: TexSlot::Some(tex_ground),
+        albedo_color: Rgb::new(255, 255, 255),
+        specular: 0.05,
+        reflectivity: 0.02,
+        transparency: 0.0,
+        ior: 1.0,
+        emission: Rgb::new(0, 0, 0),
+        emission_strength: 0.0,
+        ripple: 0.0,
+    };
+    let mat_glowstone = Material {
+        name: "glowstone",
+        tex: TexSlot::Some(tex_glowstone),
+        albedo_color: Rgb::new(255, 255, 200),
+        specular: 0.30,
+        reflectivity: 0.10,
+        transparency: 0.0,
+        ior: 1.0,
+        emission: Rgb::new(255, 240, 200),
+        emission_strength: 0.5, // brilla
+        ripple: 0.0,
+    };
+    let mat_metal = Material {
+        name: "metal",
+        ..Material::solid_with(Rgb::new(180, 180, 190), 0.75, 0.65)
+    };
+    let mat_vidrio = Material {
+        name: "vidrio",
+        tex: TexSlot::None,
+        albedo_color: Rgb::new(200, 220, 235),
+        specular: 0.25,
+        reflectivity: 0.08,
+        transparency: 0.92,
+        ior: 1.52,
+        emission: Rgb::new(0, 0, 0),
+        emission_strength: 0.0,
+        ripple: 0.0,
+    };
+    let mat_agua = Material {
+        name: "agua",
+        tex: tex_water.map_or(TexSlot::None, TexSlot::Some),
+        albedo_color: Rgb::new(110, 140, 180),
+        specular: 0.20,
+        reflectivity: 0.08,
+        transparency: 0.80,
+        ior: 1.33,
+        emission: Rgb::new(0, 0, 0),
+        emission_strength: 0.0,
+        ripple: 0.12,
+    };
+    let mat_tronco = Material {
+        name: "tronco",
+        tex: TexSlot::Some(tex_bark),
+        albedo_color: Rgb::new(255, 255, 255),
+        specular: 0.06,
+        reflectivity: 0.03,
+        transparency: 0.0,
+        ior: 1.0,
+        emission: Rgb::new(0, 0, 0),
+        emission_strength: 0.0,
+        ripple: 0.0,
+    };
+    let mat_hojas = Material {
+        name: "hojas",
+        tex: TexSlot::Some(tex_leaves),
+        albedo_color: Rgb::new(255, 255, 255),
+        specular: 0.04,
+        reflectivity: 0.02,
+        transparency: 0.0,
+        ior: 1.0,
+        emission: Rgb::new(0, 0, 0),
+        emission_strength: 0.0,
+        ripple: 0.0,
+    };
+    // Paleta del editor de bloques; el nombre de cada material se usa en el archivo de escena.
+    Ok(vec![
+        mat_brick,
+        mat_ground,
+        mat_glowstone,
+        mat_vidrio,
+        mat_agua,
+        mat_tronco,
+        mat_hojas,
+        mat_metal,
+    ])
+}
+
+// Material de la paleta por nombre; si falta, magenta como el damero.
+fn material(palette: &[Material], name: &str) -> Material {
+    palette
+        .iter()
+        .find(|m| m.name == name)
+        .cloned()
+        .unwrap_or_else(|| Material::solid(Rgb::new(255, 0, 255)))
+}
+
+// Objetos del diorama, con los materiales de `palette`.
+pub fn objects(palette: &[Material]) -> Vec<Object> {
+    let mat_brick = material(palette, "ladrillo");
+    let mat_ground = material(palette, "tierra");
+    let mat_glowstone = material(palette, "glowstone");
+    let mat_vidrio = material(palette, "vidrio");
+    let mat_agua = material(palette, "agua");
+    let mat_tronco = material(palette, "tronco");
+    let mat_hojas = material(palette, "hojas");
+
+    let mut objs: Vec<Object> = vec![];
+
+    // Parámetros “voxel”
+    let b: f32 = 0.4; // tamaño de cubo
+    let nx: i32 = 10; // ancho casa
+    let nz: i32 = 8; // profundidad casa
+    let ny: i32 = 5; // altura paredes
+
+    // Referencias
+    let world_floor_y = -0.6; // piso del mundo
+    let base_y = world_floor_y + b * 1.5; // casa subida 1 bloque
+    let house_center = vec3(0.0, 0.0, -5.0);
+
+    let halfx = (nx as f32 - 1.0) * 0.5;
+    let halfz = (nz as f32 - 1.0) * 0.5;
+    let pos = |ix: i32, iy: i32, iz: i32| -> glm::Vec3 {
+        house_center
+            + vec3(
+                (ix as f32 - halfx) * b,
+                base_y + (iy as f32) * b,
+                (iz as f32 - halfz) * b,
+            )
+    };
+
+    // Plataforma grande
+    let pad_plat = 6;
+    let nxp = nx + pad_plat * 2;
+    let nzp = nz + pad_plat * 2;
+    let plat_center = house_center;
+    let plat_y = world_floor_y + b * 0.5; // toca el mundo
+    let halfx_p = (nxp as f32 - 1.0) * 0.5;
+    let halfz_p = (nzp as f32 - 1.0) * 0.5;
+
+    for ix in 0..nxp {
+        for iz in 0..nzp {
+            let p =
+                plat_center + vec3((ix as f32 - halfx_p) * b, plat_y, (iz as f32 - halfz_p) * b);
+            let tile = Cube::from_center_size_rot(p, b, 0.0, 0.0, 0.0, mat_ground.clone());
+            objs.push(Object::Cube(tile));
+        }
+    }
+
+    // Casa (aperturas)
+    let door_w_blocks = 2;
+    let door_h_blocks = 3;
+    let door_ix_center = nx / 2;
+    let front_z = nz - 1;
+
+    let win_w_blocks = 2;
+    let win_h_blocks = 2;
+    let win_y_center = 3;
+
+    // Paredes de ladrillo con huecos
+    for iy in 0..ny {
+        for ix in 0..nx {
+            for iz in 0..nz {
+                let is_front = iz == front_z;
+                let is_back = iz == 0;
+                let is_left = ix == 0;
+                let is_right = ix == nx - 1;
+                let on_perimeter = is_front || is_back || is_left || is_right;
+                if !on_perimeter {
+                    continue;
+                }
+
+                // puerta
+                if is_front {
+                    let in_door_x = (ix >= door_ix_center - (door_w_blocks / 2))
+                        && (ix < door_ix_center + (door_w_blocks / 2));
+                    let in_door_y = iy < door_h_blocks;
+                    if in_door_x && in_door_y {
+                        continue;
+                    }
+                }
+
+                // ventanas en izq/der
+                let win_z0 = nz / 2 - win_w_blocks / 2;
+                let win_z1 = win_z0 + win_w_blocks - 1;
+                let win_y0 = win_y_center - win_h_blocks / 2;
+                let win_y1 = win_y0 + win_h_blocks - 1;
+                if (is_left || is_right)
+                    && iy >= win_y0
+                    && iy <= win_y1
+                    && iz >= win_z0
+                    && iz <= win_z1
+                {
+                    continue;
+                }
+
+                let c = Cube::from_center_size_rot(
+                    pos(ix, iy, iz),
+                    b,
+                    0.0,
+                    0.0,
+                    0.0,
+                    mat_brick.clone(),
+                );
+                objs.push(Object::Cube(c));
+            }
+        }
+    }
+
+    // Dintel
+    for ix in (door_ix_center - (door_w_blocks / 2))..=(door_ix_center + (door_w_blocks / 2) - 1) {
+        let iy = door_h_blocks;
+        let c =
+            Cube::from_center_size_rot(pos(ix, iy, front_z), b, 0.0, 0.0, 0.0, mat_brick.clone());
+        objs.push(Object::Cube(c));
+    }
+
+    // Ventanas
+    for dz in 0..win_w_blocks {
+        for dy in 0..win_h_blocks {
+            let iz = (nz / 2 - win_w_blocks / 2) + dz;
+            let iy = (win_y_center - win_h_blocks / 2) + dy;
+
+            let c_l = Cube::from_center_size_rot(
+                pos(0, iy, iz),
+                b * 0.9,
+                0.0,
+                0.0,
+                0.0,
+                mat_vidrio.clone(),
+            );
+            let c_r = Cube::from_center_size_rot(
+                pos(nx - 1, iy, iz),
+                b * 0.9,
+                0.0,
+                0.0,
+                0.0,
+                mat_vidrio.clone(),
+            );
+            objs.push(Object::Cube(c_l));
+            objs.push(Object::Cube(c_r));
+        }
+    }
+
+    // Techo con glowstone (cada bloque lleva su luz, ver más abajo)
+    let roof_y = ny;
+    for ix in 0..nx {
+        for iz in 0..nz {
+            let p = pos(ix, roof_y, iz);
+            let c = Cube::from_center_size_rot(p, b, 0.0, 0.0, 0.0, mat_glowstone.clone());
+            objs.push(Object::Cube(c));
+        }
+    }
+
+    // Árbol al frente izquierda
+    let tree_base_x = plat_center.x - (halfx_p - 2.0) * b;
+    let tree_base_z = plat_center.z + (halfz_p - 2.5) * b;
+    let tree_base_y = plat_y + b;
+
+    // Tronco
+    let trunk_h = 4;
+    for i in 0..trunk_h {
+        let p = vec3(tree_base_x, tree_base_y + (i as f32) * b, tree_base_z);
+        let trunk_block = Cube::from_center_size_rot(p, b, 0.0, 0.0, 0.0, mat_tronco.clone());
+        objs.push(Object::Cube(trunk_block));
+    }
+    // Copa
+    let crown_center_y = tree_base_y + (trunk_h as f32) * b;
+    for dx in -1..=1 {
+        for dy in -1..=1 {
+            for dz in -1..=1 {
+                let p = vec3(
+                    tree_base_x + (dx as f32) * b,
+                    crown_center_y + (dy as f32) * b,
+                    tree_base_z + (dz as f32) * b,
+                );
+                let leaf = Cube::from_center_size_rot(p, b, 0.0, 0.0, 0.0, mat_hojas.clone());
+                objs.push(Object::Cube(leaf));
+            }
+        }
+    }
+
+    // Charco junto al árbol (2x2)
+    for ax in 0..2 {
+        for az in 0..2 {
+            let p = vec3(
+                tree_base_x + (1 + ax) as f32 * b,
+                plat_y,
+                tree_base_z + az as f32 * b,
+            );
+            let water = Cube::from_center_size_rot(p, b, 0.0, 0.0, 0.0, mat_agua.clone());
+            objs.push(Object::Cube(water));
+        }
+    }
+    objs
+}
+
+// Una luz por bloque emisivo (glowstone).
+pub fn lights(objects: &[Object]) -> Vec<PointLight> {
+    objects.iter().filter_map(editor::block_light).collect()
+}
+
+// Animación del diorama: glowstone que titila, copa al viento y charco con ondas.
+pub fn timeline(objs: &[Object], lights: &[PointLight]) -> Timeline {
+    // Los grupos animados se arman por material, así valen también para escenas cargadas.
+    let with_material = |name: &str| -> Vec<usize> {
+        (0..objs.len())
+            .filter(|&i| objs[i].material().name == name)
+            .collect()
+    };
+    let glow_objs = with_material("glowstone");
+    let leaf_objs = with_material("hojas");
+    let water_objs = with_material("agua");
+    // Las pistas se aplican sobre el estado de reposo de la escena.
+    let all_lights: Vec<usize> = (0..lights.len()).collect();
+    Timeline::new(
+        objs,
+        vec![
+            // Glowstone: la emisión pulsa y las luces titilan como llamas.
+            Track::new(
+                Channel::Emission,
+                Curve::Wave {
+                    base: 0.5,
+                    amplitude: 0.15,
+                    freq: 0.25,
+                },
+                glow_objs,
+            )
+            .with_phase_step(0.07),
+            Track::new(
+                Channel::LightIntensity,
+                Curve::Flicker {
+                    base: 6.0,
+                    amount: 0.8,
+                    speed: 3.0,
+                },
+                all_lights.clone(),
+            )
+            .with_phase_step(0.37),
+            Track::new(
+                Channel::LightColor(Rgb::new(255, 220, 160), Rgb::new(255, 245, 215)),
+                Curve::Wave {
+                    base: 0.6,
+                    amplitude: 0.4,
+                    freq: 0.25,
+                },
+                all_lights,
+            )
+            .with_phase_step(0.07),
+            // Copa del árbol: se mece con el viento.
+            Track::new(
+                Channel::Offset(vec3(1.0, 0.0, 0.4)),
+                Curve::Wave {
+                    base: 0.0,
+                    amplitude: 0.015,
+                    freq: 0.35,
+                },
+                leaf_objs.clone(),
+            )
+            .with_phase_step(0.05),
+            Track::new(
+                Channel::Rotate(vec3(1.0, 0.0, 0.0)),
+                Curve::Wave {
+                    base: 0.0,
+                    amplitude: 3.0,
+                    freq: 0.35,
+                },
+                leaf_objs,
+            )
+            .with_phase_step(0.05),
+            // Charco: las ondas se avivan y se calman.
+            Track::new(
+                Channel::Ripple,
+                Curve::Wave {
+                    base: 0.12,
+                    amplitude: 0.05,
+                    freq: 0.1,
+                },
+                water_objs,
+            ),
+        ],
+    )
+}
+
+// Cielo desde `path`: un equirectangular (.hdr/.exr, o LDR: .png/.jpg/.bmp/.tga con raylib,
+// solo .png sin raylib), una cruz de cubemap 4:3 o 3:4, una carpeta con las 6 caras, o
+// `fisico` para el cielo analítico (también si falla la carga).
+pub fn sky(path: &str) -> Environment {
+    let physical_sky = || Environment::new(EnvSource::Physical(PhysicalSky::new(3.0, 14.0)));
+    if path == "fisico" {
+        return physical_sky();
+    }
+    Environment::from_file(path).unwrap_or_else(|e| {
+        eprintln!("No pude cargar el skybox ({e}); uso el cielo físico");
+        physical_sky()
+    })
+}
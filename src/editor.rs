@@ -0,0 +1,502 @@
+use crate::animation::{Timeline, TrackSlots};
+use crate::color::{Material, Rgb};
+use crate::error::Error;
+use crate::intersect::Intersect;
+use crate::objects::cube::euler_angles;
+use crate::objects::{Cube, Object};
+use crate::renderer::PointLight;
+use crate::scene::Scene;
+use glm::{Vec3, vec3};
+use nalgebra_glm as glm;
+
+// Intensidad de la luz puntual que acompaña a cada bloque emisivo (glowstone).
+const BLOCK_LIGHT_INTENSITY: f32 = 6.0;
+
+// Objeto más cercano bajo el rayo (por la BVH de la escena), con su índice en `objects`.
+pub fn pick(scene: &Scene, ro: &Vec3, rd: &Vec3) -> Option<(usize, Intersect)> {
+    match scene.intersect(ro, rd, 0.5) {
+        (Some(index), hit) => Some((index, hit)),
+        (None, _) => None,
+    }
+}
+
+// Los bloques emisivos iluminan la escena con una luz puntual en su centro.
+pub fn block_light(object: &Object) -> Option<PointLight> {
+    match object {
+        Object::Cube(c) if c.material.emission_strength > 0.0 => Some(PointLight {
+            pos: c.center,
+            color: c.material.emission,
+            intensity: BLOCK_LIGHT_INTENSITY,
+        }),
+        _ => None,
+    }
+}
+
+enum Edit {
+    Removed {
+        index: usize,
+        object: Box<Object>,
+        slots: TrackSlots,
+        light: Option<(usize, PointLight, TrackSlots)>,
+    },
+    Placed {
+        index: usize,
+        light: Option<usize>,
+    },
+}
+
+// Editor de bloques: clic izquierdo quita, derecho pone sobre la cara apuntada.
+pub struct Editor {
+    pub enabled: bool,
+    pub palette: Vec<Material>,
+    pub selected: usize,
+    undo: Vec<Edit>,
+}
+
+impl Editor {
+    pub fn new(palette: Vec<Material>) -> Self {
+        Self {
+            enabled: false,
+            palette,
+            selected: 0,
+            undo: vec![],
+        }
+    }
+
+    pub fn material(&self) -> &Material {
+        &self.palette[self.selected]
+    }
+
+    pub fn cycle(&mut self, step: i32) {
+        let n = self.palette.len() as i32;
+        self.selected = (self.selected as i32 + step).rem_euclid(n) as usize;
+    }
+
+    pub fn can_undo(&self) -> bool {
+        !self.undo.is_empty()
+    }
+
+    pub fn remove(
+        &mut self,
+        objects: &mut Vec<Object>,
+        lights: &mut Vec<PointLight>,
+        timeline: &mut Timeline,
+        index: usize,
+    ) {
+        if index >= objects.len() {
+            return;
+        }
+        let center = timeline.rest_center(index);
+        let object = objects.remove(index);
+        let slots = timeline.remove_object(index);
+        let light = block_light(&object).and_then(|_| {
+            let c = center?;
+            let li = lights
+                .iter()
+                .position(|l| glm::distance(&l.pos, &c) < 1e-4)?;
+            let l = lights.remove(li);
+            Some((li, l, timeline.remove_light(li)))
+        });
+        self.undo.push(Edit::Removed {
+            index,
+            object: Box::new(object),
+            slots,
+            light,
+        });
+    }
+
+    // Pone un bloque del material elegido pegado a la cara golpeada.
+    pub fn place(
+        &mut self,
+        objects: &mut Vec<Object>,
+        lights: &mut Vec<PointLight>,
+        timeline: &mut Timeline,
+        hit_index: usize,
+        hit: &Intersect,
+    ) {
+        let n = hit.normal;
+        let axis = if n.x.abs() >= n.y.abs() && n.x.abs() >= n.z.abs() {
+            vec3(n.x.signum(), 0.0, 0.0)
+        } else if n.y.abs() >= n.z.abs() {
+            vec3(0.0, n.y.signum(), 0.0)
+        } else {
+            vec3(0.0, 0.0, n.z.signum())
+        };
+        let (center, size) = match &objects[hit_index] {
+            Object::Cube(c) => {
+                let base = timeline.rest_center(hit_index).unwrap_or(c.center);
+                (base + axis * (c.half.x * 2.0), c.half.x * 2.0)
+            }
+            Object::Plane(_) => (hit.point + axis * 0.2, 0.4),
+        };
+        let occupied = objects
+            .iter()
+            .any(|o| matches!(o, Object::Cube(c) if glm::distance(&c.center, &center) < 1e-3));
+        if occupied {
+            return;
+        }
+        let cube = Cube::from_center_size_rot(center, size, 0.0, 0.0, 0.0, self.material().clone());
+        let object = Object::Cube(cube);
+        let index = objects.len();
+        timeline.insert_object(index, &object, &TrackSlots::default());
+        let light = block_light(&object).map(|l| {
+            lights.push(l);
+            timeline.insert_light(lights.len() - 1, &TrackSlots::default());
+            lights.len() - 1
+        });
+        objects.push(object);
+        self.undo.push(Edit::Placed { index, light });
+    }
+
+    pub fn undo(
+        &mut self,
+        objects: &mut Vec<Object>,
+        lights: &mut Vec<PointLight>,
+        timeline: &mut Timeline,
+    ) {
+        match self.undo.pop() {
+            Some(Edit::Removed {
+                index,
+                object,
+                slots,
+                light,
+            }) => {
+                timeline.insert_object(index, &object, &slots);
+                objects.insert(index.min(objects.len()), *object);
+                if let Some((li, l, lslots)) = light {
+                    timeline.insert_light(li, &lslots);
+                    lights.insert(li.min(lights.len()), l);
+                }
+            }
+            Some(Edit::Placed { index, light }) => {
+                if index < objects.len() {
+                    objects.remove(index);
+                    timeline.remove_object(index);
+                }
+                if let Some(li) = light.filter(|&li| li < lights.len()) {
+                    lights.remove(li);
+                    timeline.remove_light(li);
+                }
+            }
+            None => {}
+        }
+    }
+}
+
+// Formato, una entrada por línea; `#` inicia un comentario:
+//   material nombre r g b especular reflectividad transparencia ior er eg eb emisión ondas
+//   cubo x y z tamaño yaw pitch roll material
+// Los materiales son los de la paleta con sus valores editados en vivo (la textura sale de
+// la paleta). Se guarda la pose de reposo (sin la animación del momento). Los planos no
+// tienen entrada en el formato: una escena con planos no se guarda.
+const SCENE_HEADER: &str = "# material nombre r g b especular reflectividad transparencia ior \
+                            er eg eb emisión ondas\n# cubo x y z tamaño yaw pitch roll material\n";
+
+pub fn save_scene(
+    path: &str,
+    objects: &[Object],
+    timeline: &Timeline,
+    palette: &[Material],
+) -> Result<(), Error> {
+    let mut used: Vec<&Material> = vec![];
+    let mut cubes = String::new();
+    for (i, o) in objects.iter().enumerate() {
+        let Object::Cube(c) = o else {
+            return Err(Error::scene(
+                path,
+                format!("el objeto #{i} es un plano y el formato solo guarda cubos"),
+            ));
+        };
+        let name = c.material.name;
+        let m = palette.iter().find(|m| m.name == name).ok_or_else(|| {
+            Error::scene(
+                path,
+                format!("el material '{name}' del objeto #{i} no está en la paleta"),
+            )
+        })?;
+        if !used.iter().any(|u| u.name == name) {
+            used.push(m);
+        }
+        let p = timeline.rest_center(i).unwrap_or(c.center);
+        let (yaw, pitch, roll) = euler_angles(&timeline.rest_rotation(i).unwrap_or(c.rot));
+        cubes += &format!(
+            "cubo {:.4} {:.4} {:.4} {:.4} {:.3} {:.3} {:.3} {name}\n",
+            p.x,
+            p.y,
+            p.z,
+            c.half.x * 2.0,
+            yaw,
+            pitch,
+            roll,
+        );
+    }
+    let mut text = String::from(SCENE_HEADER);
+    for m in used {
+        text += &format!(
+            "material {} {} {} {} {:.4} {:.4} {:.4} {:.4} {} {} {} {:.4} {:.4}\n",
+            m.name,
+            m.albedo_color.r,
+            m.albedo_color.g,
+            m.albedo_color.b,
+            m.specular,
+            m.reflectivity,
+            m.transparency,
+            m.ior,
+            m.emission.r,
+            m.emission.g,
+            m.emission.b,
+            m.emission_strength,
+            m.ripple,
+        );
+    }
+    text += &cubes;
+    std::fs::write(path, text).map_err(|e| Error::write(path, e))
+}
+
+// Los valores de una línea `material`, en el orden del formato.
+fn set_material_values(m: &mut Material, v: &[f32]) {
+    let channel = |x: f32| x.round().clamp(0.0, 255.0) as u8;
+    m.albedo_color = Rgb::new(channel(v[0]), channel(v[1]), channel(v[2]));
+    m.specular = v[3];
+    m.reflectivity = v[4];
+    m.transparency = v[5];
+    m.ior = v[6];
+    m.emission = Rgb::new(channel(v[7]), channel(v[8]), channel(v[9]));
+    m.emission_strength = v[10];
+    m.ripple = v[11];
+}
+
+// Las líneas `material` se aplican sobre `palette` solo si todo el archivo se lee bien.
+// También acepta el formato anterior, `cubo x y z tamaño material` (sin rotación).
+pub fn load_scene(path: &str, palette: &mut [Material]) -> Result<Vec<Object>, Error> {
+    let text = std::fs::read_to_string(path).map_err(|e| Error::read(path, e))?;
+    let mut materials = palette.to_vec();
+    let mut objects = vec![];
+    for (i, raw) in text.lines().enumerate() {
+        let line = raw.split('#').next().unwrap_or("").trim();
+        if line.is_empty() {
+            continue;
+        }
+        let err = |msg: String| Error::scene(path, format!("línea {}: {msg}", i + 1));
+        let parts: Vec<&str> = line.split_whitespace().collect();
+        let (numbers, name) = match (parts[0], parts.len()) {
+            ("material", 14) => (&parts[2..], parts[1]),
+            ("cubo", 6 | 9) => (&parts[1..parts.len() - 1], parts[parts.len() - 1]),
+            _ => {
+                return Err(err(
+                    "se esperaba 'material nombre r g b especular reflectividad transparencia \
+                     ior er eg eb emisión ondas' o 'cubo x y z tamaño yaw pitch roll material'"
+                        .into(),
+                ));
+            }
+        };
+        let v = numbers
+            .iter()
+            .map(|p| p.parse::<f32>())
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|_| err("número inválido".into()))?;
+        let material = materials
+            .iter_mut()
+            .find(|m| m.name == name)
+            .ok_or_else(|| err(format!("material desconocido '{name}'")))?;
+        if parts[0] == "material" {
+            set_material_values(material, &v);
+            continue;
+        }
+        let (yaw, pitch, roll) = match v[..] {
+            [_, _, _, _, yaw, pitch, roll] => (yaw, pitch, roll),
+            _ => (0.0, 0.0, 0.0),
+        };
+        let cube = Cube::from_center_size_rot(
+            vec3(v[0], v[1], v[2]),
+            v[3],
+            yaw,
+            pitch,
+            roll,
+            material.clone(),
+        );
+        objects.push(Object::Cube(cube));
+    }
+    palette.clone_from_slice(&materials);
+    Ok(objects)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::animation::{Channel, Curve, Track};
+    use crate::intersect::RayIntersect;
+    use crate::objects::Plane;
+
+    fn palette() -> Vec<Material> {
+        vec![
+            Material {
+                name: "piedra",
+                ..Material::solid(Rgb::new(120, 120, 120))
+            },
+            Material {
+                name: "glowstone",
+                emission: Rgb::new(255, 200, 120),
+                emission_strength: 1.5,
+                ..Material::solid(Rgb::new(240, 220, 150))
+            },
+        ]
+    }
+
+    fn cube(palette: &[Material], x: f32, material: usize) -> Object {
+        Object::Cube(Cube::from_center_size_rot(
+            vec3(x, 0.0, 0.0),
+            1.0,
+            30.0,
+            20.0,
+            10.0,
+            palette[material].clone(),
+        ))
+    }
+
+    // Tres cubos (el del medio emisivo) y una luz suelta antes de la del bloque, todo animado.
+    fn scene() -> (Vec<Object>, Vec<PointLight>, Timeline) {
+        let palette = palette();
+        let objects = vec![
+            cube(&palette, 0.0, 0),
+            cube(&palette, 1.0, 1),
+            cube(&palette, 2.0, 0),
+        ];
+        let mut lights = vec![PointLight {
+            pos: vec3(0.0, 5.0, 0.0),
+            color: Rgb::new(255, 255, 255),
+            intensity: 1.0,
+        }];
+        lights.extend(objects.iter().filter_map(block_light));
+        let tracks = vec![
+            Track::new(
+                Channel::Offset(vec3(0.0, 1.0, 0.0)),
+                Curve::Spin { deg_per_sec: 1.0 },
+                vec![0, 1, 2],
+            ),
+            Track::new(
+                Channel::LightIntensity,
+                Curve::Spin { deg_per_sec: 1.0 },
+                vec![0, 1],
+            ),
+        ];
+        let timeline = Timeline::new(&objects, tracks);
+        (objects, lights, timeline)
+    }
+
+    #[test]
+    fn quitar_y_deshacer_repone_objeto_luz_y_pistas() {
+        let (mut objects, mut lights, mut timeline) = scene();
+        timeline.apply(&mut objects, &mut lights, 2.0);
+        let mut editor = Editor::new(palette());
+
+        editor.remove(&mut objects, &mut lights, &mut timeline, 1);
+        assert_eq!(objects.len(), 2);
+        assert_eq!(lights.len(), 1, "la luz del bloque se va con él");
+        assert_eq!(timeline.tracks[0].targets, vec![0, 1]);
+        assert_eq!(timeline.tracks[1].targets, vec![0]);
+        assert_eq!(timeline.rest_center(1), Some(vec3(2.0, 0.0, 0.0)));
+
+        editor.undo(&mut objects, &mut lights, &mut timeline);
+        assert!(!editor.can_undo());
+        assert_eq!(objects.len(), 3);
+        assert_eq!(objects[1].material().name, "glowstone");
+        assert_eq!(lights.len(), 2);
+        assert_eq!(lights[1].pos, vec3(1.0, 0.0, 0.0));
+        assert_eq!(timeline.tracks[0].targets, vec![0, 1, 2]);
+        assert_eq!(timeline.tracks[1].targets, vec![0, 1]);
+        assert_eq!(timeline.rest_center(1), Some(vec3(1.0, 0.0, 0.0)));
+    }
+
+    #[test]
+    fn pick_da_el_objeto_mas_cercano() {
+        let (objects, _, _) = scene();
+        let scene = Scene::builder().with_objects(objects).build();
+        let (index, hit) = pick(&scene, &vec3(5.0, 0.0, 0.0), &vec3(-1.0, 0.0, 0.0)).unwrap();
+        assert_eq!(index, 2);
+        assert!(hit.distance < 3.0);
+        assert!(pick(&scene, &vec3(0.0, 5.0, 0.0), &vec3(0.0, 1.0, 0.0)).is_none());
+    }
+
+    #[test]
+    fn poner_y_deshacer_deja_todo_como_estaba() {
+        let (mut objects, mut lights, mut timeline) = scene();
+        let mut editor = Editor::new(palette());
+        editor.selected = 1;
+        let hit = objects[0].ray_intersect(&vec3(0.0, 5.0, 0.0), &vec3(0.0, -1.0, 0.0));
+        editor.place(&mut objects, &mut lights, &mut timeline, 0, &hit);
+        assert_eq!(objects.len(), 4);
+        assert_eq!(lights.len(), 3);
+        assert_eq!(timeline.tracks[1].targets, vec![0, 1]);
+
+        editor.undo(&mut objects, &mut lights, &mut timeline);
+        assert_eq!((objects.len(), lights.len()), (3, 2));
+        assert_eq!(timeline.rest_center(3), None);
+    }
+
+    #[test]
+    fn guardar_y_cargar_conserva_pose_y_materiales() {
+        let (mut objects, mut lights, timeline) = scene();
+        timeline.apply(&mut objects, &mut lights, 2.0);
+        let mut edited = palette();
+        edited[0].specular = 0.5;
+        edited[0].albedo_color = Rgb::new(10, 20, 30);
+        let path = std::env::temp_dir().join(format!("escena_{}.txt", std::process::id()));
+        let path = path.to_str().unwrap();
+        save_scene(path, &objects, &timeline, &edited).unwrap();
+
+        let mut loaded_palette = palette();
+        let loaded = load_scene(path, &mut loaded_palette).unwrap();
+        std::fs::remove_file(path).unwrap();
+        assert_eq!(loaded_palette[0].specular, 0.5);
+        assert_eq!(loaded_palette[0].albedo_color, Rgb::new(10, 20, 30));
+        assert_eq!(loaded_palette[1].emission_strength, 1.5);
+        assert_eq!(loaded.len(), 3);
+        for (i, o) in loaded.iter().enumerate() {
+            let Object::Cube(c) = o else { unreachable!() };
+            assert!(glm::distance(&c.center, &vec3(i as f32, 0.0, 0.0)) < 1e-3);
+            assert!((c.rot - timeline.rest_rotation(i).unwrap()).abs().max() < 1e-4);
+            assert_eq!(c.material.name, objects[i].material().name);
+        }
+        assert_eq!(loaded[0].material().specular, 0.5);
+    }
+
+    #[test]
+    fn lo_que_el_formato_no_representa_no_se_guarda() {
+        let palette = palette();
+        let path = std::env::temp_dir().join(format!("escena_no_{}.txt", std::process::id()));
+        let path = path.to_str().unwrap();
+        let plane = Object::Plane(Plane::new(
+            vec3(0.0, -1.0, 0.0),
+            vec3(0.0, 1.0, 0.0),
+            palette[0].clone(),
+        ));
+        let timeline = Timeline::default();
+        assert!(save_scene(path, &[plane], &timeline, &palette).is_err());
+        let foreign = Object::Cube(Cube::from_center_size_rot(
+            vec3(0.0, 0.0, 0.0),
+            1.0,
+            0.0,
+            0.0,
+            0.0,
+            Material::solid(Rgb::new(1, 2, 3)),
+        ));
+        assert!(save_scene(path, &[foreign], &timeline, &palette).is_err());
+        assert!(!std::path::Path::new(path).exists());
+    }
+
+    #[test]
+    fn carga_el_formato_sin_rotacion() {
+        let path = std::env::temp_dir().join(format!("escena_vieja_{}.txt", std::process::id()));
+        let path = path.to_str().unwrap();
+        std::fs::write(path, "cubo 1 2 3 0.5 piedra\n").unwrap();
+        let loaded = load_scene(path, &mut palette()).unwrap();
+        std::fs::write(path, "cubo 1 2 3 0.5 madera\n").unwrap();
+        assert!(load_scene(path, &mut palette()).is_err());
+        std::fs::remove_file(path).unwrap();
+        let Object::Cube(c) = &loaded[0] else {
+            unreachable!()
+        };
+        assert_eq!((c.center, c.half.x), (vec3(1.0, 2.0, 3.0), 0.25));
+    }
+}
@@ -0,0 +1,377 @@
+use crate::cubemap::CubeMap;
+use crate::error::Error;
+use crate::sky::PhysicalSky;
+use crate::texture::HdrTexture;
+use glm::{Vec3, vec3};
+use nalgebra_glm as glm;
+use std::f32::consts::PI;
+
+// Resolución del mapa horneado que se usa para iluminar (no para el fondo).
+const BAKE_W: usize = 128;
+const BAKE_H: usize = 64;
+const BAKE_SUPERSAMPLE: usize = 4;
+const SPEC_W: usize = 64;
+const SPEC_H: usize = 32;
+// Exponente Phong de cada nivel prefiltrado: roughness 0 -> 256, roughness 1 -> 4.
+const SPEC_EXPONENTS: [i32; 4] = [256, 64, 16, 4];
+
+pub enum EnvSource {
+    Equirect(HdrTexture),
+    Physical(PhysicalSky),
+    Cubemap(CubeMap),
+}
+
+impl EnvSource {
+    fn radiance(&self, dir: &Vec3) -> Vec3 {
+        match self {
+            EnvSource::Equirect(tex) => tex.sample_dir_equirect(dir),
+            EnvSource::Physical(sky) => sky.radiance(dir),
+            EnvSource::Cubemap(cube) => cube.sample(dir),
+        }
+    }
+
+    fn lighting_radiance(&self, dir: &Vec3) -> Vec3 {
+        match self {
+            EnvSource::Physical(sky) => sky.sky_radiance(dir),
+            _ => self.radiance(dir),
+        }
+    }
+}
+
+// Mapa lat-long en flotante: theta desde +Y por filas, phi por columnas.
+struct LatLong {
+    w: usize,
+    h: usize,
+    texels: Vec<Vec3>,
+}
+
+impl LatLong {
+    fn dir_at(&self, x: f32, y: f32) -> Vec3 {
+        let theta = y / self.h as f32 * PI;
+        let phi = x / self.w as f32 * 2.0 * PI;
+        vec3(
+            theta.sin() * phi.cos(),
+            theta.cos(),
+            theta.sin() * phi.sin(),
+        )
+    }
+
+    fn texel_dir(&self, x: usize, y: usize) -> Vec3 {
+        self.dir_at(x as f32 + 0.5, y as f32 + 0.5)
+    }
+
+    fn texel_solid_angle(&self, y: usize) -> f32 {
+        let theta = (y as f32 + 0.5) / self.h as f32 * PI;
+        (2.0 * PI / self.w as f32) * (PI / self.h as f32) * theta.sin()
+    }
+
+    fn sample(&self, dir: &Vec3) -> Vec3 {
+        let d = glm::normalize(dir);
+        let theta = d.y.clamp(-1.0, 1.0).acos();
+        let mut phi = d.z.atan2(d.x);
+        if phi < 0.0 {
+            phi += 2.0 * PI;
+        }
+        let fx = phi / (2.0 * PI) * self.w as f32 - 0.5;
+        let fy = (theta / PI * self.h as f32 - 0.5).clamp(0.0, (self.h - 1) as f32);
+        let x0 = fx.floor();
+        let y0 = fy.floor() as usize;
+        let tx = fx - x0;
+        let ty = fy - y0 as f32;
+        let y1 = (y0 + 1).min(self.h - 1);
+        let wrap = |x: f32| (x as i32).rem_euclid(self.w as i32) as usize;
+        let (xa, xb) = (wrap(x0), wrap(x0 + 1.0));
+        let at = |x: usize, y: usize| self.texels[y * self.w + x];
+        let top = at(xa, y0) * (1.0 - tx) + at(xb, y0) * tx;
+        let bottom = at(xa, y1) * (1.0 - tx) + at(xb, y1) * tx;
+        top * (1.0 - ty) + bottom * ty
+    }
+}
+
+// CDF marginal (filas) + condicional (columnas) sobre luminancia * sin(theta).
+struct Distribution2D {
+    marginal: Vec<f32>,
+    conditional: Vec<Vec<f32>>,
+    weights: Vec<f32>,
+    total: f32,
+}
+
+fn build_cdf(weights: &[f32]) -> (Vec<f32>, f32) {
+    let mut cdf = Vec::with_capacity(weights.len());
+    let mut acc = 0.0;
+    for w in weights {
+        acc += w;
+        cdf.push(acc);
+    }
+    if acc > 0.0 {
+        for c in cdf.iter_mut() {
+            *c /= acc;
+        }
+    }
+    (cdf, acc)
+}
+
+fn search_cdf(cdf: &[f32], u: f32) -> usize {
+    cdf.partition_point(|&c| c < u).min(cdf.len() - 1)
+}
+
+impl Distribution2D {
+    fn new(map: &LatLong) -> Self {
+        let mut weights = Vec::with_capacity(map.w * map.h);
+        for y in 0..map.h {
+            let sin_theta = ((y as f32 + 0.5) / map.h as f32 * PI).sin();
+            for x in 0..map.w {
+                weights.push(luminance(&map.texels[y * map.w + x]) * sin_theta + 1e-6);
+            }
+        }
+        let mut row_sums = Vec::with_capacity(map.h);
+        let mut conditional = Vec::with_capacity(map.h);
+        for y in 0..map.h {
+            let (cdf, sum) = build_cdf(&weights[y * map.w..(y + 1) * map.w]);
+            conditional.push(cdf);
+            row_sums.push(sum);
+        }
+        let (marginal, total) = build_cdf(&row_sums);
+        Self {
+            marginal,
+            conditional,
+            weights,
+            total,
+        }
+    }
+}
+
+pub fn luminance(c: &Vec3) -> f32 {
+    0.2126 * c.x + 0.7152 * c.y + 0.0722 * c.z
+}
+
+pub struct EnvSample {
+    pub dir: Vec3,
+    pub pdf: f32,
+    pub radiance: Vec3,
+}
+
+pub struct Environment {
+    source: EnvSource,
+    pub rotation: f32,
+    pub intensity: f32,
+    baked: LatLong,
+    sh: [Vec3; 9],
+    specular_levels: Vec<LatLong>,
+    dist: Distribution2D,
+}
+
+impl Environment {
+    pub fn new(source: EnvSource) -> Self {
+        let baked = bake(&source);
+        let sh = project_sh9(&baked);
+        let specular_levels = prefilter_levels(&baked);
+        let dist = Distribution2D::new(&baked);
+        Self {
+            source,
+            rotation: 0.0,
+            intensity: 1.0,
+            baked,
+            sh,
+            specular_levels,
+            dist,
+        }
+    }
+
+    // Recalcula SH, niveles especulares y CDF tras cambiar la fuente (p.ej. la hora).
+    pub fn rebake(&mut self) {
+        self.baked = bake(&self.source);
+        self.sh = project_sh9(&self.baked);
+        self.specular_levels = prefilter_levels(&self.baked);
+        self.dist = Distribution2D::new(&self.baked);
+    }
+
+    pub fn physical_sky(&self) -> Option<&PhysicalSky> {
+        match &self.source {
+            EnvSource::Physical(sky) => Some(sky),
+            _ => None,
+        }
+    }
+
+    pub fn physical_sky_mut(&mut self) -> Option<&mut PhysicalSky> {
+        match &mut self.source {
+            EnvSource::Physical(sky) => Some(sky),
+            _ => None,
+        }
+    }
+
+    // Dirección hacia el sol en coordenadas de mundo (respeta la rotación del cielo).
+    pub fn sun_dir(&self) -> Option<Vec3> {
+        self.physical_sky()
+            .map(|sky| rotate_y(&sky.sun_dir(), self.rotation))
+    }
+
+    // .hdr / .exr en flotante; cualquier otra extensión es LDR (ver `Texture::from_file`:
+    // con raylib PNG, JPG, BMP, TGA...; sin raylib solo PNG).
+    // Una carpeta se lee como 6 caras de cubemap y una imagen 4:3 / 3:4 como cruz.
+    pub fn from_file(path: &str) -> Result<Self, Error> {
+        if std::path::Path::new(path).is_dir() {
+            return Ok(Self::new(EnvSource::Cubemap(CubeMap::from_dir(path)?)));
+        }
+        let tex = HdrTexture::from_file(path)?;
+        if CubeMap::is_cross_layout(tex.w, tex.h) {
+            let cube = CubeMap::from_cross(&tex).map_err(|e| Error::format(path, e))?;
+            return Ok(Self::new(EnvSource::Cubemap(cube)));
+        }
+        Ok(Self::new(EnvSource::Equirect(tex)))
+    }
+
+    pub fn rotate_deg(&mut self, deg: f32) {
+        self.rotation = (self.rotation + deg.to_radians()).rem_euclid(2.0 * PI);
+    }
+
+    fn to_local(&self, dir: &Vec3) -> Vec3 {
+        rotate_y(dir, -self.rotation)
+    }
+
+    // Radiancia del fondo (rayos que no chocan con nada).
+    pub fn radiance(&self, dir: &Vec3) -> Vec3 {
+        self.source.radiance(&self.to_local(dir)) * self.intensity
+    }
+
+    // Irradiancia difusa ya dividida por pi: se multiplica directo por el albedo.
+    pub fn irradiance(&self, n: &Vec3) -> Vec3 {
+        let d = self.to_local(n);
+        let basis = sh9_basis(&d);
+        let bands = [PI, 2.0 * PI / 3.0, PI / 4.0];
+        let mut e = vec3(0.0, 0.0, 0.0);
+        for (i, coeff) in self.sh.iter().enumerate() {
+            let band = match i {
+                0 => 0,
+                1..=3 => 1,
+                _ => 2,
+            };
+            e += coeff * (bands[band] * basis[i]);
+        }
+        (e / PI).map(|c| c.max(0.0)) * self.intensity
+    }
+
+    // Radiancia especular prefiltrada para una dirección reflejada.
+    pub fn specular(&self, dir: &Vec3, roughness: f32) -> Vec3 {
+        let d = self.to_local(dir);
+        let t = roughness.clamp(0.0, 1.0) * (self.specular_levels.len() - 1) as f32;
+        let i0 = t.floor() as usize;
+        let i1 = (i0 + 1).min(self.specular_levels.len() - 1);
+        let f = t - i0 as f32;
+        let a = self.specular_levels[i0].sample(&d);
+        let b = self.specular_levels[i1].sample(&d);
+        (a * (1.0 - f) + b * f) * self.intensity
+    }
+
+    // Muestreo por importancia según el brillo del mapa horneado.
+    pub fn sample(&self, u1: f32, u2: f32) -> EnvSample {
+        let y = search_cdf(&self.dist.marginal, u1);
+        let x = search_cdf(&self.dist.conditional[y], u2);
+        let w = self.baked.w;
+        let h = self.baked.h;
+        let weight = self.dist.weights[y * w + x];
+        let pdf_uv = weight / self.dist.total * (w * h) as f32;
+        let theta = (y as f32 + 0.5) / h as f32 * PI;
+        let pdf = pdf_uv / (2.0 * PI * PI * theta.sin().max(1e-4));
+        let local = self.baked.texel_dir(x, y);
+        EnvSample {
+            dir: rotate_y(&local, self.rotation),
+            pdf,
+            radiance: self.baked.texels[y * w + x] * self.intensity,
+        }
+    }
+}
+
+fn rotate_y(v: &Vec3, angle: f32) -> Vec3 {
+    let (s, c) = angle.sin_cos();
+    vec3(c * v.x + s * v.z, v.y, -s * v.x + c * v.z)
+}
+
+fn bake(source: &EnvSource) -> LatLong {
+    let mut map = LatLong {
+        w: BAKE_W,
+        h: BAKE_H,
+        texels: vec![vec3(0.0, 0.0, 0.0); BAKE_W * BAKE_H],
+    };
+    let n = BAKE_SUPERSAMPLE;
+    for y in 0..BAKE_H {
+        for x in 0..BAKE_W {
+            let mut acc = vec3(0.0, 0.0, 0.0);
+            for sy in 0..n {
+                for sx in 0..n {
+                    let fx = x as f32 + (sx as f32 + 0.5) / n as f32;
+                    let fy = y as f32 + (sy as f32 + 0.5) / n as f32;
+                    acc += source.lighting_radiance(&map.dir_at(fx, fy));
+                }
+            }
+            map.texels[y * BAKE_W + x] = acc / (n * n) as f32;
+        }
+    }
+    map
+}
+
+fn sh9_basis(d: &Vec3) -> [f32; 9] {
+    [
+        0.282095,
+        0.488603 * d.y,
+        0.488603 * d.z,
+        0.488603 * d.x,
+        1.092548 * d.x * d.y,
+        1.092548 * d.y * d.z,
+        0.315392 * (3.0 * d.z * d.z - 1.0),
+        1.092548 * d.x * d.z,
+        0.546274 * (d.x * d.x - d.y * d.y),
+    ]
+}
+
+fn project_sh9(map: &LatLong) -> [Vec3; 9] {
+    let mut sh = [vec3(0.0, 0.0, 0.0); 9];
+    for y in 0..map.h {
+        let d_omega = map.texel_solid_angle(y);
+        for x in 0..map.w {
+            let basis = sh9_basis(&map.texel_dir(x, y));
+            let l = map.texels[y * map.w + x];
+            for (coeff, b) in sh.iter_mut().zip(basis.iter()) {
+                *coeff += l * (b * d_omega);
+            }
+        }
+    }
+    sh
+}
+
+fn prefilter_levels(src: &LatLong) -> Vec<LatLong> {
+    SPEC_EXPONENTS.iter().map(|&n| prefilter(src, n)).collect()
+}
+
+fn prefilter(src: &LatLong, exponent: i32) -> LatLong {
+    let dirs: Vec<Vec3> = (0..src.w * src.h)
+        .map(|i| src.texel_dir(i % src.w, i / src.w))
+        .collect();
+    let mut out = LatLong {
+        w: SPEC_W,
+        h: SPEC_H,
+        texels: vec![vec3(0.0, 0.0, 0.0); SPEC_W * SPEC_H],
+    };
+    for y in 0..SPEC_H {
+        for x in 0..SPEC_W {
+            let r = out.texel_dir(x, y);
+            let mut acc = vec3(0.0, 0.0, 0.0);
+            let mut wsum = 0.0;
+            for (i, d) in dirs.iter().enumerate() {
+                let c = glm::dot(&r, d);
+                if c <= 0.0 {
+                    continue;
+                }
+                let w = c.powi(exponent) * src.texel_solid_angle(i / src.w);
+                acc += src.texels[i] * w;
+                wsum += w;
+            }
+            out.texels[y * SPEC_W + x] = if wsum > 0.0 {
+                acc / wsum
+            } else {
+                src.sample(&r)
+            };
+        }
+    }
+    out
+}
@@ -0,0 +1,102 @@
+use std::fmt;
+
+// Errores al cargar o guardar archivos. Los de archivo llevan la ruta para poder mostrarlos
+// tal cual.
+#[derive(Debug)]
+pub enum Error {
+    // El archivo (o carpeta) no existe.
+    Missing {
+        path: String,
+    },
+    // Existe pero no se pudo leer.
+    Read {
+        path: String,
+        source: std::io::Error,
+    },
+    // Formato desconocido o archivo corrupto.
+    Format {
+        path: String,
+        msg: String,
+    },
+    // Escena, recorrido o configuración con datos inválidos (el mensaje trae la línea).
+    Scene {
+        path: String,
+        msg: String,
+    },
+    // No se pudo escribir el archivo.
+    Write {
+        path: String,
+        source: std::io::Error,
+    },
+    // Falla de raylib sin archivo de por medio (p.ej. subir una textura a la GPU).
+    Gpu {
+        msg: String,
+    },
+}
+
+impl Error {
+    // Error de lectura; "no existe" se separa del resto.
+    pub fn read(path: &str, source: std::io::Error) -> Self {
+        let path = path.to_string();
+        if source.kind() == std::io::ErrorKind::NotFound {
+            Error::Missing { path }
+        } else {
+            Error::Read { path, source }
+        }
+    }
+
+    pub fn format(path: &str, msg: impl fmt::Display) -> Self {
+        Error::Format {
+            path: path.to_string(),
+            msg: msg.to_string(),
+        }
+    }
+
+    pub fn scene(path: &str, msg: impl fmt::Display) -> Self {
+        Error::Scene {
+            path: path.to_string(),
+            msg: msg.to_string(),
+        }
+    }
+
+    // raylib no distingue "no existe" de "no se pudo decodificar": se mira el disco.
+    #[cfg(feature = "raylib")]
+    pub fn load(path: &str, msg: impl fmt::Display) -> Self {
+        if std::path::Path::new(path).exists() {
+            Self::format(path, msg)
+        } else {
+            Error::Missing {
+                path: path.to_string(),
+            }
+        }
+    }
+
+    pub fn write(path: &str, source: std::io::Error) -> Self {
+        Error::Write {
+            path: path.to_string(),
+            source,
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::Missing { path } => write!(f, "{path}: no existe"),
+            Error::Read { path, source } => write!(f, "{path}: {source}"),
+            Error::Format { path, msg } => write!(f, "{path}: {msg}"),
+            Error::Scene { path, msg } => write!(f, "{path}: {msg}"),
+            Error::Write { path, source } => write!(f, "{path}: no se pudo escribir ({source})"),
+            Error::Gpu { msg } => write!(f, "GPU: {msg}"),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Read { source, .. } | Error::Write { source, .. } => Some(source),
+            _ => None,
+        }
+    }
+}
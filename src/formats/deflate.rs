@@ -0,0 +1,246 @@
+// Compresor DEFLATE/zlib mínimo (RFC 1950/1951): LZ77 voraz con cadenas de hash y
+// un solo bloque de códigos Huffman fijos. Comprime bastante menos que zlib, pero los
+// renders (cielo, paredes lisas) tienen mucha repetición y alcanza.
+
+const WINDOW: usize = 32 * 1024;
+const MIN_MATCH: usize = 3;
+const MAX_MATCH: usize = 258;
+// Candidatos que se prueban por posición; más = mejor compresión y más lento.
+const MAX_CHAIN: usize = 64;
+const HASH_BITS: u32 = 15;
+
+// (base, bits extra) de los códigos de longitud 257..285 y de distancia 0..29.
+const LEN_BASE: [u16; 29] = [
+    3, 4, 5, 6, 7, 8, 9, 10, 11, 13, 15, 17, 19, 23, 27, 31, 35, 43, 51, 59, 67, 83, 99, 115, 131,
+    163, 195, 227, 258,
+];
+const LEN_EXTRA: [u8; 29] = [
+    0, 0, 0, 0, 0, 0, 0, 0, 1, 1, 1, 1, 2, 2, 2, 2, 3, 3, 3, 3, 4, 4, 4, 4, 5, 5, 5, 5, 0,
+];
+const DIST_BASE: [u16; 30] = [
+    1, 2, 3, 4, 5, 7, 9, 13, 17, 25, 33, 49, 65, 97, 129, 193, 257, 385, 513, 769, 1025, 1537,
+    2049, 3073, 4097, 6145, 8193, 12289, 16385, 24577,
+];
+const DIST_EXTRA: [u8; 30] = [
+    0, 0, 0, 0, 1, 1, 2, 2, 3, 3, 4, 4, 5, 5, 6, 6, 7, 7, 8, 8, 9, 9, 10, 10, 11, 11, 12, 12, 13,
+    13,
+];
+
+struct BitWriter {
+    out: Vec<u8>,
+    bit_buf: u64,
+    bit_cnt: u32,
+}
+
+impl BitWriter {
+    fn new() -> Self {
+        Self {
+            out: Vec::new(),
+            bit_buf: 0,
+            bit_cnt: 0,
+        }
+    }
+
+    // Valores (bits extra, cabeceras): el bit menos significativo primero.
+    fn bits(&mut self, value: u32, n: u32) {
+        self.bit_buf |= (value as u64) << self.bit_cnt;
+        self.bit_cnt += n;
+        while self.bit_cnt >= 8 {
+            self.out.push(self.bit_buf as u8);
+            self.bit_buf >>= 8;
+            self.bit_cnt -= 8;
+        }
+    }
+
+    // Códigos Huffman: el más significativo primero.
+    fn code(&mut self, code: u32, len: u32) {
+        self.bits(code.reverse_bits() >> (32 - len), len);
+    }
+
+    fn finish(mut self) -> Vec<u8> {
+        if self.bit_cnt > 0 {
+            self.out.push(self.bit_buf as u8);
+        }
+        self.out
+    }
+}
+
+// Código fijo de un literal/longitud (0..=287).
+fn fixed_lit(w: &mut BitWriter, sym: u32) {
+    match sym {
+        0..=143 => w.code(0x30 + sym, 8),
+        144..=255 => w.code(0x190 + sym - 144, 9),
+        256..=279 => w.code(sym - 256, 7),
+        _ => w.code(0xc0 + sym - 280, 8),
+    }
+}
+
+fn write_match(w: &mut BitWriter, len: usize, dist: usize) {
+    let li = LEN_BASE
+        .iter()
+        .rposition(|&b| b as usize <= len)
+        .unwrap_or(0);
+    fixed_lit(w, 257 + li as u32);
+    w.bits((len - LEN_BASE[li] as usize) as u32, LEN_EXTRA[li] as u32);
+    let di = DIST_BASE
+        .iter()
+        .rposition(|&b| b as usize <= dist)
+        .unwrap_or(0);
+    w.code(di as u32, 5);
+    w.bits(
+        (dist - DIST_BASE[di] as usize) as u32,
+        DIST_EXTRA[di] as u32,
+    );
+}
+
+fn hash(data: &[u8], i: usize) -> usize {
+    let v = u32::from_le_bytes([data[i], data[i + 1], data[i + 2], 0]);
+    (v.wrapping_mul(2654435761) >> (32 - HASH_BITS)) as usize
+}
+
+pub fn deflate(data: &[u8]) -> Vec<u8> {
+    let mut w = BitWriter::new();
+    w.bits(1, 1); // último bloque
+    w.bits(1, 2); // Huffman fijo
+
+    // `head[h]` = última posición con ese hash; `prev[i % WINDOW]` = la anterior a `i`.
+    let mut head = vec![usize::MAX; 1 << HASH_BITS];
+    let mut prev = vec![usize::MAX; WINDOW];
+    let insert = |i: usize, head: &mut [usize], prev: &mut [usize]| {
+        if i + MIN_MATCH <= data.len() {
+            let h = hash(data, i);
+            prev[i % WINDOW] = head[h];
+            head[h] = i;
+        }
+    };
+
+    let mut i = 0;
+    while i < data.len() {
+        let (mut best_len, mut best_dist) = (0, 0);
+        if i + MIN_MATCH <= data.len() {
+            let max = MAX_MATCH.min(data.len() - i);
+            let mut cand = head[hash(data, i)];
+            let mut chain = 0;
+            while cand != usize::MAX && i - cand <= WINDOW && chain < MAX_CHAIN {
+                let len = data[cand..]
+                    .iter()
+                    .zip(&data[i..i + max])
+                    .take_while(|(a, b)| a == b)
+                    .count();
+                if len > best_len {
+                    (best_len, best_dist) = (len, i - cand);
+                    if len == max {
+                        break;
+                    }
+                }
+                let next = prev[cand % WINDOW];
+                // La tabla circular puede traer una posición más nueva: fin de la cadena.
+                if next >= cand {
+                    break;
+                }
+                cand = next;
+                chain += 1;
+            }
+        }
+        if best_len >= MIN_MATCH {
+            write_match(&mut w, best_len, best_dist);
+            for j in i..i + best_len {
+                insert(j, &mut head, &mut prev);
+            }
+            i += best_len;
+        } else {
+            fixed_lit(&mut w, data[i] as u32);
+            insert(i, &mut head, &mut prev);
+            i += 1;
+        }
+    }
+    fixed_lit(&mut w, 256);
+    w.finish()
+}
+
+fn adler32(data: &[u8]) -> u32 {
+    let (mut a, mut b) = (1u32, 0u32);
+    for chunk in data.chunks(5552) {
+        for &x in chunk {
+            a += x as u32;
+            b += a;
+        }
+        a %= 65521;
+        b %= 65521;
+    }
+    (b << 16) | a
+}
+
+pub fn zlib_compress(data: &[u8]) -> Vec<u8> {
+    // CM = 8, ventana de 32K, sin diccionario; FCHECK hace la cabecera múltiplo de 31.
+    let mut out = vec![0x78, 0x01];
+    out.extend(deflate(data));
+    out.extend(adler32(data).to_be_bytes());
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::formats::inflate::zlib_decompress;
+
+    // Bytes pseudoaleatorios (xorshift): casi no se repiten.
+    fn noise(n: usize) -> Vec<u8> {
+        let mut s = 0x2545_f491u32;
+        (0..n)
+            .map(|_| {
+                s ^= s << 13;
+                s ^= s >> 17;
+                s ^= s << 5;
+                s as u8
+            })
+            .collect()
+    }
+
+    #[test]
+    fn comprimir_y_descomprimir() {
+        let repeated: Vec<u8> = b"cielo azul, pasto verde; "
+            .iter()
+            .cycle()
+            .take(100_000)
+            .copied()
+            .collect();
+        // Más largo que la ventana: las copias no pueden ir más atrás de 32K.
+        let mut mixed = noise(40_000);
+        mixed.extend_from_within(..40_000);
+        mixed.extend(noise(3));
+        for data in [
+            vec![],
+            vec![7],
+            b"aaa".to_vec(),
+            repeated.clone(),
+            noise(70_000),
+            mixed,
+        ] {
+            let z = zlib_compress(&data);
+            assert_eq!(
+                zlib_decompress(&z, data.len()).unwrap(),
+                data,
+                "{} bytes",
+                data.len()
+            );
+        }
+        assert!(zlib_compress(&repeated).len() < repeated.len() / 20);
+    }
+
+    #[test]
+    fn adler32_conocido() {
+        assert_eq!(adler32(b""), 1);
+        assert_eq!(adler32(b"Wikipedia"), 0x11e6_0398);
+        // Más de 5552 bytes: la reducción por bloques no debe desbordar.
+        assert_eq!(adler32(&[255; 20_000]), 0x9f51_d664);
+    }
+
+    #[test]
+    fn flujo_cortado_da_error() {
+        let z = zlib_compress(&noise(1000));
+        // Sin los 4 bytes del Adler y el último byte del bloque.
+        assert!(zlib_decompress(&z[..z.len() - 5], 1 << 20).is_err());
+        assert!(zlib_decompress(&z[..2], 1 << 20).is_err());
+    }
+}
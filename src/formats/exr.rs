@@ -0,0 +1,532 @@
+// Lector de OpenEXR de una sola parte por scanlines.
+// Compresiones soportadas: NONE, RLE, ZIPS y ZIP. Canales R/G/B (o Y) en HALF, FLOAT o UINT.
+// El escritor (`encode`) genera la misma variante sin compresión y con canales FLOAT.
+use super::inflate::zlib_decompress;
+use glm::{Vec3, vec3};
+use nalgebra_glm as glm;
+
+const MAGIC: [u8; 4] = [0x76, 0x2f, 0x31, 0x01];
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PixelType {
+    Uint,
+    Half,
+    Float,
+}
+
+impl PixelType {
+    fn from_i32(v: i32) -> Result<Self, String> {
+        match v {
+            0 => Ok(PixelType::Uint),
+            1 => Ok(PixelType::Half),
+            2 => Ok(PixelType::Float),
+            _ => Err(format!("tipo de píxel EXR inválido: {v}")),
+        }
+    }
+
+    pub fn size(self) -> usize {
+        match self {
+            PixelType::Half => 2,
+            _ => 4,
+        }
+    }
+}
+
+struct Channel {
+    name: String,
+    ty: PixelType,
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, n: usize) -> Result<&'a [u8], String> {
+        let end = self.pos.checked_add(n).ok_or("EXR truncado")?;
+        let s = self.bytes.get(self.pos..end).ok_or("EXR truncado")?;
+        self.pos = end;
+        Ok(s)
+    }
+    fn i32(&mut self) -> Result<i32, String> {
+        let b = self.take(4)?;
+        Ok(i32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+    }
+    fn u64(&mut self) -> Result<u64, String> {
+        let b = self.take(8)?;
+        let mut a = [0u8; 8];
+        a.copy_from_slice(b);
+        Ok(u64::from_le_bytes(a))
+    }
+    fn cstr(&mut self) -> Result<String, String> {
+        let start = self.pos;
+        while *self.bytes.get(self.pos).ok_or("EXR truncado")? != 0 {
+            self.pos += 1;
+        }
+        let s = String::from_utf8_lossy(&self.bytes[start..self.pos]).to_string();
+        self.pos += 1;
+        Ok(s)
+    }
+}
+
+pub fn half_to_f32(h: u16) -> f32 {
+    let sign = ((h >> 15) & 1) as u32;
+    let exp = ((h >> 10) & 0x1f) as u32;
+    let mant = (h & 0x3ff) as u32;
+    let bits = if exp == 0 {
+        if mant == 0 {
+            sign << 31
+        } else {
+            // subnormal: normalizar
+            let mut e = 127 - 15 + 1;
+            let mut m = mant;
+            while m & 0x400 == 0 {
+                m <<= 1;
+                e -= 1;
+            }
+            (sign << 31) | ((e as u32) << 23) | ((m & 0x3ff) << 13)
+        }
+    } else if exp == 0x1f {
+        (sign << 31) | (0xff << 23) | (mant << 13)
+    } else {
+        (sign << 31) | ((exp + 127 - 15) << 23) | (mant << 13)
+    };
+    f32::from_bits(bits)
+}
+
+fn parse_channels(data: &[u8]) -> Result<Vec<Channel>, String> {
+    let mut r = Reader {
+        bytes: data,
+        pos: 0,
+    };
+    let mut channels = vec![];
+    loop {
+        let name = r.cstr()?;
+        if name.is_empty() {
+            break;
+        }
+        let ty = PixelType::from_i32(r.i32()?)?;
+        r.take(4)?; // pLinear + reservado
+        let xs = r.i32()?;
+        let ys = r.i32()?;
+        if xs != 1 || ys != 1 {
+            return Err(format!("canal EXR submuestreado no soportado: {name}"));
+        }
+        channels.push(Channel { name, ty });
+    }
+    Ok(channels)
+}
+
+// Deshace el predictor y el entrelazado que usan RLE y ZIP.
+fn undo_predictor_interleave(t: &mut [u8]) -> Vec<u8> {
+    for i in 1..t.len() {
+        t[i] = (t[i - 1] as i32 + t[i] as i32 - 128) as u8;
+    }
+    let n = t.len();
+    let mut out = Vec::with_capacity(n);
+    let (mut t1, mut t2) = (0, n.div_ceil(2));
+    while out.len() < n {
+        out.push(t[t1]);
+        t1 += 1;
+        if out.len() < n {
+            out.push(t[t2]);
+            t2 += 1;
+        }
+    }
+    out
+}
+
+fn rle_decompress(data: &[u8], expected: usize) -> Result<Vec<u8>, String> {
+    // Cada par de bytes da a lo sumo 128: el tamaño del bloque no se cree a ciegas.
+    let mut out = Vec::with_capacity(expected.min(data.len() * 64));
+    let mut i = 0;
+    while i < data.len() {
+        if out.len() > expected {
+            return Err("RLE EXR más largo de lo esperado".into());
+        }
+        let count = data[i] as i8;
+        i += 1;
+        if count < 0 {
+            let n = (-(count as i32)) as usize;
+            out.extend_from_slice(data.get(i..i + n).ok_or("RLE EXR truncado")?);
+            i += n;
+        } else {
+            let v = *data.get(i).ok_or("RLE EXR truncado")?;
+            i += 1;
+            out.extend(std::iter::repeat_n(v, count as usize + 1));
+        }
+    }
+    Ok(out)
+}
+
+pub fn decode(bytes: &[u8]) -> Result<(usize, usize, Vec<Vec3>), String> {
+    let mut r = Reader { bytes, pos: 0 };
+    if r.take(4)? != MAGIC {
+        return Err("no es un archivo OpenEXR".into());
+    }
+    let version = r.i32()?;
+    if version & 0xff != 2 {
+        return Err(format!("versión EXR no soportada: {}", version & 0xff));
+    }
+    if version & 0x200 != 0 {
+        return Err("EXR por tiles no soportado".into());
+    }
+    if version & 0x1800 != 0 {
+        return Err("EXR multiparte/deep no soportado".into());
+    }
+
+    let mut channels = None;
+    let mut compression = None;
+    let mut data_window = None;
+    loop {
+        let name = r.cstr()?;
+        if name.is_empty() {
+            break;
+        }
+        let _ty = r.cstr()?;
+        let size = r.i32()?;
+        if size < 0 {
+            return Err("atributo EXR con tamaño negativo".into());
+        }
+        let value = r.take(size as usize)?;
+        match name.as_str() {
+            "channels" => channels = Some(parse_channels(value)?),
+            "compression" => compression = value.first().copied(),
+            "dataWindow" => {
+                let mut vr = Reader {
+                    bytes: value,
+                    pos: 0,
+                };
+                data_window = Some((vr.i32()?, vr.i32()?, vr.i32()?, vr.i32()?));
+            }
+            _ => {}
+        }
+    }
+    let channels = channels.ok_or("EXR sin atributo channels")?;
+    let compression = compression.ok_or("EXR sin atributo compression")?;
+    let (x_min, y_min, x_max, y_max) = data_window.ok_or("EXR sin atributo dataWindow")?;
+    // Ventana con límites inclusivos; se calcula con chequeo porque viene del archivo.
+    let extent = |min: i32, max: i32| {
+        max.checked_sub(min)
+            .and_then(|d| d.checked_add(1))
+            .ok_or("dataWindow EXR inválido")
+    };
+    let (w, h) = (extent(x_min, x_max)?, extent(y_min, y_max)?);
+    if w <= 0 || h <= 0 {
+        return Err("imagen EXR vacía".into());
+    }
+    let (w, h) = (w as usize, h as usize);
+    let lines_per_block = match compression {
+        0..=2 => 1,
+        3 => 16,
+        c => return Err(format!("compresión EXR no soportada (código {c})")),
+    };
+
+    let find = |n: &str| {
+        channels
+            .iter()
+            .position(|c| c.name == n || c.name.ends_with(&format!(".{n}")))
+    };
+    let rgb = match (find("R"), find("G"), find("B"), find("Y")) {
+        (Some(r), Some(g), Some(b), _) => [r, g, b],
+        (_, _, _, Some(y)) => [y, y, y],
+        _ => return Err("EXR sin canales R/G/B ni Y".into()),
+    };
+
+    let bytes_per_line = channels
+        .iter()
+        .try_fold(0usize, |acc, c| {
+            c.ty.size().checked_mul(w)?.checked_add(acc)
+        })
+        .ok_or("imagen EXR demasiado grande")?;
+    let blocks = h.div_ceil(lines_per_block);
+    if blocks * 8 > bytes.len() - r.pos {
+        return Err("EXR truncado (tabla de offsets)".into());
+    }
+    let mut block_data = Vec::with_capacity(blocks);
+    for _ in 0..blocks {
+        let mut cr = Reader {
+            bytes,
+            pos: r.u64()? as usize,
+        };
+        let y0 = cr.i32()?.checked_sub(y_min).ok_or("bloque EXR inválido")?;
+        let size = cr.i32()?;
+        if size < 0 || y0 < 0 {
+            return Err("bloque EXR inválido".into());
+        }
+        block_data.push((y0 as usize, cr.take(size as usize)?));
+    }
+
+    // Lo más que puede crecer un bloque al descomprimirse: RLE da 128 bytes por cada 2 y
+    // deflate unos 1032 por byte. Una cabecera que pide más de lo que caben en los bloques
+    // se rechaza antes de reservar la imagen.
+    let ratio = match compression {
+        0 => 1,
+        1 => 64,
+        _ => 1032,
+    };
+    let total: usize = block_data.iter().map(|(_, raw)| raw.len()).sum();
+    let len = w.checked_mul(h).ok_or("imagen EXR demasiado grande")?;
+    if bytes_per_line
+        .checked_mul(h)
+        .is_none_or(|n| n > total.saturating_mul(ratio))
+    {
+        return Err("EXR truncado (bloques más chicos que la imagen)".into());
+    }
+    let mut pixels = vec![vec3(0.0, 0.0, 0.0); len];
+    for (y0, raw) in block_data {
+        let lines = lines_per_block.min(h.saturating_sub(y0));
+        let expected = bytes_per_line * lines;
+        let data = if raw.len() == expected {
+            raw.to_vec()
+        } else {
+            let mut t = match compression {
+                1 => rle_decompress(raw, expected)?,
+                2 | 3 => zlib_decompress(raw, expected)?,
+                _ => return Err("bloque EXR sin comprimir con tamaño incorrecto".into()),
+            };
+            undo_predictor_interleave(&mut t)
+        };
+        if data.len() < expected {
+            return Err("bloque EXR descomprimido demasiado corto".into());
+        }
+
+        let mut p = 0;
+        for line in 0..lines {
+            let row = y0 + line;
+            for (ci, ch) in channels.iter().enumerate() {
+                for x in 0..w {
+                    let v = match ch.ty {
+                        PixelType::Half => half_to_f32(u16::from_le_bytes([data[p], data[p + 1]])),
+                        PixelType::Float => {
+                            f32::from_le_bytes([data[p], data[p + 1], data[p + 2], data[p + 3]])
+                        }
+                        PixelType::Uint => {
+                            u32::from_le_bytes([data[p], data[p + 1], data[p + 2], data[p + 3]])
+                                as f32
+                        }
+                    };
+                    p += ch.ty.size();
+                    let px = &mut pixels[row * w + x];
+                    for (k, &idx) in rgb.iter().enumerate() {
+                        if idx == ci {
+                            px[k] = v;
+                        }
+                    }
+                }
+            }
+        }
+    }
+    Ok((w, h, pixels))
+}
+
+fn write_attr(out: &mut Vec<u8>, name: &str, ty: &str, value: &[u8]) {
+    out.extend_from_slice(name.as_bytes());
+    out.push(0);
+    out.extend_from_slice(ty.as_bytes());
+    out.push(0);
+    out.extend_from_slice(&(value.len() as i32).to_le_bytes());
+    out.extend_from_slice(value);
+}
+
+// `channels` = (nombre, valores fila por fila). Las capas se nombran `capa.R`; el formato
+// exige los canales ordenados por nombre, así que se ordenan aquí.
+pub fn encode(width: usize, height: usize, channels: &[(String, Vec<f32>)]) -> Vec<u8> {
+    let mut chans: Vec<&(String, Vec<f32>)> = channels.iter().collect();
+    chans.sort_by(|a, b| a.0.as_bytes().cmp(b.0.as_bytes()));
+
+    let mut out = MAGIC.to_vec();
+    // Versión 2, scanlines; el bit 0x400 permite nombres de más de 31 caracteres.
+    let long_names = chans.iter().any(|c| c.0.len() > 31);
+    let version: i32 = 2 | if long_names { 0x400 } else { 0 };
+    out.extend_from_slice(&version.to_le_bytes());
+
+    let mut chlist = vec![];
+    for (name, _) in chans.iter() {
+        chlist.extend_from_slice(name.as_bytes());
+        chlist.push(0);
+        chlist.extend_from_slice(&2i32.to_le_bytes()); // FLOAT
+        chlist.extend_from_slice(&[0, 0, 0, 0]); // pLinear + reservado
+        chlist.extend_from_slice(&1i32.to_le_bytes());
+        chlist.extend_from_slice(&1i32.to_le_bytes());
+    }
+    chlist.push(0);
+    let window: Vec<u8> = [0, 0, width as i32 - 1, height as i32 - 1]
+        .iter()
+        .flat_map(|v| v.to_le_bytes())
+        .collect();
+    write_attr(&mut out, "channels", "chlist", &chlist);
+    write_attr(&mut out, "compression", "compression", &[0]);
+    write_attr(&mut out, "dataWindow", "box2i", &window);
+    write_attr(&mut out, "displayWindow", "box2i", &window);
+    write_attr(&mut out, "lineOrder", "lineOrder", &[0]);
+    let one = 1.0f32.to_le_bytes();
+    write_attr(&mut out, "pixelAspectRatio", "float", &one);
+    write_attr(&mut out, "screenWindowCenter", "v2f", &[0; 8]);
+    write_attr(&mut out, "screenWindowWidth", "float", &one);
+    out.push(0);
+
+    // Tabla de offsets y luego un bloque por scanline: y, tamaño, canales en orden.
+    let line_bytes = chans.len() * width * 4;
+    let table_end = out.len() + height * 8;
+    for y in 0..height {
+        let offset = (table_end + y * (8 + line_bytes)) as u64;
+        out.extend_from_slice(&offset.to_le_bytes());
+    }
+    for y in 0..height {
+        out.extend_from_slice(&(y as i32).to_le_bytes());
+        out.extend_from_slice(&(line_bytes as i32).to_le_bytes());
+        for (_, values) in chans.iter() {
+            for x in 0..width {
+                let v = values.get(y * width + x).copied().unwrap_or(0.0);
+                out.extend_from_slice(&v.to_le_bytes());
+            }
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::formats::deflate::zlib_compress;
+
+    fn gradient(w: usize, h: usize) -> Vec<(String, Vec<f32>)> {
+        let ramp = |k: f32| (0..w * h).map(|i| i as f32 * k - 1.5).collect();
+        vec![
+            ("R".into(), ramp(0.25)),
+            ("G".into(), ramp(-2.0)),
+            ("B".into(), ramp(1e3)),
+            ("A".into(), vec![1.0; w * h]),
+        ]
+    }
+
+    // Posición del valor del atributo `name` en un archivo de `encode`.
+    fn attr(bytes: &[u8], name: &str, ty: &str) -> usize {
+        let key = [name.as_bytes(), &[0], ty.as_bytes(), &[0]].concat();
+        let at = bytes.windows(key.len()).position(|w| w == key).unwrap();
+        at + key.len() + 4
+    }
+
+    fn set_i32(bytes: &mut [u8], at: usize, v: i32) {
+        bytes[at..at + 4].copy_from_slice(&v.to_le_bytes());
+    }
+
+    // Reescribe un archivo de `encode` con compresión ZIPS (una scanline por bloque).
+    fn to_zips(bytes: &[u8], height: usize) -> Vec<u8> {
+        // `screenWindowWidth` es el último atributo; después vienen el 0 y la tabla.
+        let table = attr(bytes, "screenWindowWidth", "float") + 5;
+        let mut out = bytes[..table].to_vec();
+        out[attr(bytes, "compression", "compression")] = 2;
+        let mut blocks = vec![];
+        let mut pos = table + height * 8;
+        for _ in 0..height {
+            let size = i32::from_le_bytes(bytes[pos + 4..pos + 8].try_into().unwrap()) as usize;
+            let raw = &bytes[pos + 8..pos + 8 + size];
+            // Lo inverso del lector: bytes pares y luego impares, y diferencias + 128.
+            let mut t: Vec<u8> = raw
+                .iter()
+                .step_by(2)
+                .chain(raw.iter().skip(1).step_by(2))
+                .copied()
+                .collect();
+            for i in (1..t.len()).rev() {
+                t[i] = t[i].wrapping_sub(t[i - 1]).wrapping_add(128);
+            }
+            let z = zlib_compress(&t);
+            blocks.push([&bytes[pos..pos + 4], &(z.len() as i32).to_le_bytes(), &z].concat());
+            pos += 8 + size;
+        }
+        let mut offset = (table + height * 8) as u64;
+        for b in &blocks {
+            out.extend_from_slice(&offset.to_le_bytes());
+            offset += b.len() as u64;
+        }
+        out.extend(blocks.concat());
+        out
+    }
+
+    fn check(pixels: &[Vec3], chans: &[(String, Vec<f32>)]) {
+        for (i, p) in pixels.iter().enumerate() {
+            assert_eq!(
+                *p,
+                vec3(chans[0].1[i], chans[1].1[i], chans[2].1[i]),
+                "píxel {i}"
+            );
+        }
+    }
+
+    #[test]
+    fn encode_y_decode() {
+        let (w, h) = (5, 3);
+        let chans = gradient(w, h);
+        let (dw, dh, pixels) = decode(&encode(w, h, &chans)).unwrap();
+        assert_eq!((dw, dh), (w, h));
+        check(&pixels, &chans);
+    }
+
+    #[test]
+    fn bloques_zips() {
+        let (w, h) = (7, 4);
+        let chans = gradient(w, h);
+        let bytes = to_zips(&encode(w, h, &chans), h);
+        let (_, _, pixels) = decode(&bytes).unwrap();
+        check(&pixels, &chans);
+    }
+
+    #[test]
+    fn solo_luminancia() {
+        let chans = vec![("Y".to_string(), vec![0.5, 2.0])];
+        let (_, _, pixels) = decode(&encode(2, 1, &chans)).unwrap();
+        assert_eq!(pixels, vec![vec3(0.5, 0.5, 0.5), vec3(2.0, 2.0, 2.0)]);
+    }
+
+    #[test]
+    fn half_a_f32() {
+        assert_eq!(half_to_f32(0x3c00), 1.0);
+        assert_eq!(half_to_f32(0xc000), -2.0);
+        assert_eq!(half_to_f32(0x0001), 2.0f32.powi(-24));
+        assert_eq!(half_to_f32(0x7c00), f32::INFINITY);
+    }
+
+    #[test]
+    fn archivo_truncado_da_error() {
+        let bytes = encode(4, 4, &gradient(4, 4));
+        for cut in [3, 20, 200, bytes.len() - 1] {
+            assert!(decode(&bytes[..cut]).is_err(), "cortado en {cut}");
+        }
+        let zips = to_zips(&bytes, 4);
+        assert!(decode(&zips[..zips.len() - 3]).is_err());
+        assert!(decode(b"\x76\x2f\x31\x02").is_err());
+    }
+
+    #[test]
+    fn data_window_hostil_da_error() {
+        let bytes = encode(2, 2, &gradient(2, 2));
+        let window = attr(&bytes, "dataWindow", "box2i");
+
+        let mut wide = bytes.clone();
+        set_i32(&mut wide, window, i32::MIN);
+        set_i32(&mut wide, window + 8, i32::MAX);
+        assert!(decode(&wide).is_err());
+
+        // Ancho enorme que cabe en i32 pero no en los bloques del archivo.
+        let mut huge = bytes.clone();
+        set_i32(&mut huge, window + 8, i32::MAX - 1);
+        assert!(decode(&huge).is_err());
+
+        let mut empty = bytes.clone();
+        set_i32(&mut empty, window + 12, -1);
+        assert!(decode(&empty).is_err());
+
+        // Bloque con una y que desborda al restarle y_min.
+        let mut shifted = bytes.clone();
+        set_i32(&mut shifted, window + 4, 1);
+        set_i32(&mut shifted, window + 12, 2);
+        let table = attr(&bytes, "screenWindowWidth", "float") + 5;
+        let first = u64::from_le_bytes(bytes[table..table + 8].try_into().unwrap()) as usize;
+        set_i32(&mut shifted, first, i32::MIN);
+        assert!(decode(&shifted).is_err());
+    }
+}
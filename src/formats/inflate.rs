@@ -0,0 +1,322 @@
+// Descompresor DEFLATE/zlib mínimo (RFC 1950/1951), al estilo de puff.c.
+
+struct BitReader<'a> {
+    data: &'a [u8],
+    pos: usize,
+    bit_buf: u32,
+    bit_cnt: u32,
+}
+
+impl<'a> BitReader<'a> {
+    fn new(data: &'a [u8]) -> Self {
+        Self {
+            data,
+            pos: 0,
+            bit_buf: 0,
+            bit_cnt: 0,
+        }
+    }
+
+    fn bits(&mut self, need: u32) -> Result<u32, String> {
+        while self.bit_cnt < need {
+            let byte = *self.data.get(self.pos).ok_or("deflate: datos truncados")?;
+            self.pos += 1;
+            self.bit_buf |= (byte as u32) << self.bit_cnt;
+            self.bit_cnt += 8;
+        }
+        let v = self.bit_buf & ((1u32 << need) - 1);
+        self.bit_buf >>= need;
+        self.bit_cnt -= need;
+        Ok(v)
+    }
+
+    fn align_byte(&mut self) {
+        self.bit_buf = 0;
+        self.bit_cnt = 0;
+    }
+}
+
+const MAX_BITS: usize = 15;
+
+struct Huffman {
+    counts: [u16; MAX_BITS + 1],
+    symbols: Vec<u16>,
+}
+
+impl Huffman {
+    fn new(lengths: &[u8]) -> Result<Self, String> {
+        let mut counts = [0u16; MAX_BITS + 1];
+        for &l in lengths {
+            counts[l as usize] += 1;
+        }
+        counts[0] = 0;
+        let mut offs = [0u16; MAX_BITS + 1];
+        for len in 1..MAX_BITS {
+            offs[len + 1] = offs[len] + counts[len];
+        }
+        let mut symbols = vec![0u16; lengths.len()];
+        for (sym, &l) in lengths.iter().enumerate() {
+            if l != 0 {
+                symbols[offs[l as usize] as usize] = sym as u16;
+                offs[l as usize] += 1;
+            }
+        }
+        Ok(Self { counts, symbols })
+    }
+
+    fn decode(&self, br: &mut BitReader) -> Result<u16, String> {
+        let mut code: i32 = 0;
+        let mut first: i32 = 0;
+        let mut index: i32 = 0;
+        for len in 1..=MAX_BITS {
+            code |= br.bits(1)? as i32;
+            let count = self.counts[len] as i32;
+            if code - count < first {
+                return Ok(self.symbols[(index + (code - first)) as usize]);
+            }
+            index += count;
+            first += count;
+            first <<= 1;
+            code <<= 1;
+        }
+        Err("deflate: código Huffman inválido".into())
+    }
+}
+
+const LEN_BASE: [u16; 29] = [
+    3, 4, 5, 6, 7, 8, 9, 10, 11, 13, 15, 17, 19, 23, 27, 31, 35, 43, 51, 59, 67, 83, 99, 115, 131,
+    163, 195, 227, 258,
+];
+const LEN_EXTRA: [u8; 29] = [
+    0, 0, 0, 0, 0, 0, 0, 0, 1, 1, 1, 1, 2, 2, 2, 2, 3, 3, 3, 3, 4, 4, 4, 4, 5, 5, 5, 5, 0,
+];
+const DIST_BASE: [u16; 30] = [
+    1, 2, 3, 4, 5, 7, 9, 13, 17, 25, 33, 49, 65, 97, 129, 193, 257, 385, 513, 769, 1025, 1537,
+    2049, 3073, 4097, 6145, 8193, 12289, 16385, 24577,
+];
+const DIST_EXTRA: [u8; 30] = [
+    0, 0, 0, 0, 1, 1, 2, 2, 3, 3, 4, 4, 5, 5, 6, 6, 7, 7, 8, 8, 9, 9, 10, 10, 11, 11, 12, 12, 13,
+    13,
+];
+
+const TOO_LONG: &str = "deflate: datos más largos de lo esperado";
+
+fn inflate_codes(
+    br: &mut BitReader,
+    out: &mut Vec<u8>,
+    limit: usize,
+    lencode: &Huffman,
+    distcode: &Huffman,
+) -> Result<(), String> {
+    loop {
+        let sym = lencode.decode(br)? as usize;
+        if sym < 256 {
+            if out.len() >= limit {
+                return Err(TOO_LONG.into());
+            }
+            out.push(sym as u8);
+        } else if sym == 256 {
+            return Ok(());
+        } else {
+            let i = sym - 257;
+            if i >= 29 {
+                return Err("deflate: longitud inválida".into());
+            }
+            let len = LEN_BASE[i] as usize + br.bits(LEN_EXTRA[i] as u32)? as usize;
+            let d = distcode.decode(br)? as usize;
+            if d >= 30 {
+                return Err("deflate: distancia inválida".into());
+            }
+            let dist = DIST_BASE[d] as usize + br.bits(DIST_EXTRA[d] as u32)? as usize;
+            if dist > out.len() {
+                return Err("deflate: distancia fuera de rango".into());
+            }
+            if out.len() + len > limit {
+                return Err(TOO_LONG.into());
+            }
+            let start = out.len() - dist;
+            for k in 0..len {
+                out.push(out[start + k]);
+            }
+        }
+    }
+}
+
+fn fixed_tables() -> Result<(Huffman, Huffman), String> {
+    let mut lengths = [0u8; 288];
+    for (i, l) in lengths.iter_mut().enumerate() {
+        *l = match i {
+            0..=143 => 8,
+            144..=255 => 9,
+            256..=279 => 7,
+            _ => 8,
+        };
+    }
+    Ok((Huffman::new(&lengths)?, Huffman::new(&[5u8; 30])?))
+}
+
+fn dynamic_tables(br: &mut BitReader) -> Result<(Huffman, Huffman), String> {
+    const ORDER: [usize; 19] = [
+        16, 17, 18, 0, 8, 7, 9, 6, 10, 5, 11, 4, 12, 3, 13, 2, 14, 1, 15,
+    ];
+    let nlen = br.bits(5)? as usize + 257;
+    let ndist = br.bits(5)? as usize + 1;
+    let ncode = br.bits(4)? as usize + 4;
+    let mut lengths = [0u8; 320];
+    for &idx in ORDER.iter().take(ncode) {
+        lengths[idx] = br.bits(3)? as u8;
+    }
+    let lencode = Huffman::new(&lengths[..19])?;
+
+    let mut index = 0;
+    while index < nlen + ndist {
+        let sym = lencode.decode(br)?;
+        if sym < 16 {
+            lengths[index] = sym as u8;
+            index += 1;
+            continue;
+        }
+        let (value, repeat) = match sym {
+            16 => {
+                if index == 0 {
+                    return Err("deflate: repetición sin longitud previa".into());
+                }
+                (lengths[index - 1], 3 + br.bits(2)? as usize)
+            }
+            17 => (0, 3 + br.bits(3)? as usize),
+            _ => (0, 11 + br.bits(7)? as usize),
+        };
+        if index + repeat > nlen + ndist {
+            return Err("deflate: demasiadas longitudes".into());
+        }
+        for _ in 0..repeat {
+            lengths[index] = value;
+            index += 1;
+        }
+    }
+    Ok((
+        Huffman::new(&lengths[..nlen])?,
+        Huffman::new(&lengths[nlen..nlen + ndist])?,
+    ))
+}
+
+// `limit`: tamaño máximo de la salida (el que permite la cabecera del archivo), para que
+// unos pocos bytes comprimidos no puedan pedir gigas de memoria.
+pub fn inflate(data: &[u8], limit: usize) -> Result<Vec<u8>, String> {
+    let mut br = BitReader::new(data);
+    let mut out = Vec::new();
+    loop {
+        let last = br.bits(1)?;
+        match br.bits(2)? {
+            0 => {
+                br.align_byte();
+                let p = br.pos;
+                if p + 4 > data.len() {
+                    return Err("deflate: bloque almacenado truncado".into());
+                }
+                let len = u16::from_le_bytes([data[p], data[p + 1]]) as usize;
+                let end = p + 4 + len;
+                if end > data.len() {
+                    return Err("deflate: bloque almacenado truncado".into());
+                }
+                if out.len() + len > limit {
+                    return Err(TOO_LONG.into());
+                }
+                out.extend_from_slice(&data[p + 4..end]);
+                br.pos = end;
+            }
+            1 => {
+                let (l, d) = fixed_tables()?;
+                inflate_codes(&mut br, &mut out, limit, &l, &d)?;
+            }
+            2 => {
+                let (l, d) = dynamic_tables(&mut br)?;
+                inflate_codes(&mut br, &mut out, limit, &l, &d)?;
+            }
+            _ => return Err("deflate: tipo de bloque inválido".into()),
+        }
+        if last == 1 {
+            return Ok(out);
+        }
+    }
+}
+
+pub fn zlib_decompress(data: &[u8], limit: usize) -> Result<Vec<u8>, String> {
+    if data.len() < 2
+        || data[0] & 0x0f != 8
+        || !u16::from_be_bytes([data[0], data[1]]).is_multiple_of(31)
+    {
+        return Err("zlib: cabecera inválida".into());
+    }
+    if data[1] & 0x20 != 0 {
+        return Err("zlib: diccionario predefinido no soportado".into());
+    }
+    inflate(&data[2..], limit)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Generados con zlib (deflate crudo, nivel 9).
+    const FIXED: [u8; 15] = [75, 76, 42, 74, 76, 78, 76, 73, 4, 82, 10, 137, 216, 217, 0];
+    const FIXED_TEXT: &[u8] = b"abracadabra abracadabra abracadabra";
+    const DYNAMIC: [u8; 28] = [
+        5, 193, 201, 1, 0, 16, 12, 0, 176, 89, 227, 168, 82, 246, 255, 74, 100, 211, 143, 154, 226,
+        145, 118, 55, 202, 13, 203, 7,
+    ];
+    const DYNAMIC_TEXT: &[u8] = b"ahbacjakeafmaahaicadkalfaga";
+
+    #[test]
+    fn bloque_almacenado() {
+        // Un bloque almacenado no final y otro final: LEN, NLEN y los bytes tal cual.
+        let data = [
+            0, 2, 0, 0xfd, 0xff, b'h', b'o', 1, 2, 0, 0xfd, 0xff, b'l', b'a',
+        ];
+        assert_eq!(inflate(&data, 4).unwrap(), b"hola");
+    }
+
+    #[test]
+    fn bloque_fijo() {
+        assert_eq!(inflate(&FIXED, FIXED_TEXT.len()).unwrap(), FIXED_TEXT);
+    }
+
+    #[test]
+    fn bloque_dinamico() {
+        assert_eq!(inflate(&DYNAMIC, DYNAMIC_TEXT.len()).unwrap(), DYNAMIC_TEXT);
+    }
+
+    #[test]
+    fn zlib_con_cabecera() {
+        let mut data = vec![0x78, 0xda];
+        data.extend(FIXED);
+        assert_eq!(zlib_decompress(&data, 100).unwrap(), FIXED_TEXT);
+        assert!(zlib_decompress(&[0x78, 0x00, 0x01], 100).is_err());
+        assert!(zlib_decompress(&[0x78, 0xbb, 0, 0, 0, 0], 100).is_err());
+        assert!(zlib_decompress(&[0x78], 100).is_err());
+    }
+
+    #[test]
+    fn datos_truncados_dan_error() {
+        for data in [&FIXED[..], &DYNAMIC[..], &[1, 4, 0, 0xfb, 0xff, 1, 2, 3, 4]] {
+            for cut in 0..data.len() - 1 {
+                assert!(inflate(&data[..cut], 100).is_err(), "cortado en {cut}");
+            }
+        }
+    }
+
+    #[test]
+    fn datos_invalidos_dan_error() {
+        // Tipo de bloque 3.
+        assert!(inflate(&[0x07], 100).is_err());
+        // Bloque fijo que empieza con una copia (longitud 3, distancia 1) sin nada antes.
+        assert!(inflate(&[0x03, 0x02, 0x00], 100).is_err());
+    }
+
+    #[test]
+    fn salida_mas_larga_que_el_limite_da_error() {
+        assert!(inflate(&FIXED, FIXED_TEXT.len() - 1).is_err());
+        assert!(inflate(&DYNAMIC, 10).is_err());
+        assert!(inflate(&[1, 4, 0, 0xfb, 0xff, 1, 2, 3, 4], 3).is_err());
+    }
+}
@@ -0,0 +1,5 @@
+pub mod deflate;
+pub mod exr;
+pub mod inflate;
+pub mod png;
+pub mod rgbe;
@@ -0,0 +1,467 @@
+// Lector y escritor de PNG. Lee todos los tipos de color y profundidades (incluido
+// Adam7) y devuelve RGBA de 8 bits; escribe RGB de 8 bits con filtro por fila.
+use super::{deflate, inflate};
+
+const SIGNATURE: [u8; 8] = [0x89, b'P', b'N', b'G', b'\r', b'\n', 0x1a, b'\n'];
+// (x0, y0, dx, dy) de las 7 pasadas de Adam7.
+const ADAM7: [(usize, usize, usize, usize); 7] = [
+    (0, 0, 8, 8),
+    (4, 0, 8, 8),
+    (0, 4, 4, 8),
+    (2, 0, 4, 4),
+    (0, 2, 2, 4),
+    (1, 0, 2, 2),
+    (0, 1, 1, 2),
+];
+
+fn crc32(data: &[u8]) -> u32 {
+    let mut crc = 0xffff_ffffu32;
+    for &b in data {
+        crc ^= b as u32;
+        for _ in 0..8 {
+            crc = if crc & 1 != 0 {
+                (crc >> 1) ^ 0xedb8_8320
+            } else {
+                crc >> 1
+            };
+        }
+    }
+    !crc
+}
+
+fn paeth(a: u8, b: u8, c: u8) -> u8 {
+    let p = a as i16 + b as i16 - c as i16;
+    let (pa, pb, pc) = (
+        (p - a as i16).abs(),
+        (p - b as i16).abs(),
+        (p - c as i16).abs(),
+    );
+    if pa <= pb && pa <= pc {
+        a
+    } else if pb <= pc {
+        b
+    } else {
+        c
+    }
+}
+
+struct Header {
+    width: usize,
+    height: usize,
+    depth: u8,
+    color: u8,
+    interlaced: bool,
+}
+
+impl Header {
+    fn channels(&self) -> usize {
+        match self.color {
+            0 | 3 => 1,
+            2 => 3,
+            4 => 2,
+            _ => 4,
+        }
+    }
+
+    // Bytes por píxel para los filtros (al menos 1 con profundidades menores a 8).
+    fn filter_bpp(&self) -> usize {
+        (self.channels() * self.depth as usize).div_ceil(8)
+    }
+
+    fn row_bytes(&self, w: usize) -> usize {
+        (w * self.channels() * self.depth as usize).div_ceil(8)
+    }
+}
+
+// Deshace los filtros de una imagen (o pasada) de `w` x `h` y devuelve las filas crudas.
+fn unfilter(data: &[u8], h: &Header, w: usize, rows: usize) -> Result<Vec<u8>, String> {
+    let (stride, bpp) = (h.row_bytes(w), h.filter_bpp());
+    if data.len() < rows * (stride + 1) {
+        return Err("PNG: datos de imagen truncados".into());
+    }
+    let mut out = vec![0u8; rows * stride];
+    for y in 0..rows {
+        let filter = data[y * (stride + 1)];
+        let src = &data[y * (stride + 1) + 1..(y + 1) * (stride + 1)];
+        let (done, cur) = out.split_at_mut(y * stride);
+        let prev = if y > 0 {
+            &done[(y - 1) * stride..]
+        } else {
+            &[][..]
+        };
+        let cur = &mut cur[..stride];
+        for x in 0..stride {
+            let a = if x >= bpp { cur[x - bpp] } else { 0 };
+            let b = prev.get(x).copied().unwrap_or(0);
+            let c = if x >= bpp {
+                prev.get(x - bpp).copied().unwrap_or(0)
+            } else {
+                0
+            };
+            cur[x] = src[x].wrapping_add(match filter {
+                0 => 0,
+                1 => a,
+                2 => b,
+                3 => ((a as u16 + b as u16) / 2) as u8,
+                4 => paeth(a, b, c),
+                f => return Err(format!("PNG: filtro desconocido {f}")),
+            });
+        }
+    }
+    Ok(out)
+}
+
+// Muestra `i` de una fila, llevada a 8 bits.
+fn sample(row: &[u8], i: usize, depth: u8) -> u8 {
+    match depth {
+        8 => row[i],
+        16 => row[i * 2],
+        d => {
+            let per_byte = 8 / d as usize;
+            let shift = 8 - d as usize * (i % per_byte + 1);
+            let v = (row[i / per_byte] >> shift) & ((1 << d) - 1);
+            // Escala 1/2/4 bits a 0..255 repitiendo el patrón de bits.
+            (v as u32 * 255 / ((1 << d) - 1)) as u8
+        }
+    }
+}
+
+// Índice de paleta sin escalar (profundidades de 1 a 8 bits).
+fn index(row: &[u8], i: usize, depth: u8) -> usize {
+    if depth == 8 {
+        return row[i] as usize;
+    }
+    let per_byte = 8 / depth as usize;
+    let shift = 8 - depth as usize * (i % per_byte + 1);
+    ((row[i / per_byte] >> shift) & ((1 << depth) - 1)) as usize
+}
+
+pub fn decode(bytes: &[u8]) -> Result<(usize, usize, Vec<[u8; 4]>), String> {
+    if !bytes.starts_with(&SIGNATURE) {
+        return Err("no es un archivo PNG".into());
+    }
+    let mut pos = SIGNATURE.len();
+    let mut header = None;
+    let mut palette: Vec<[u8; 4]> = vec![];
+    let mut idat = vec![];
+    while pos + 8 <= bytes.len() {
+        let len = u32::from_be_bytes(bytes[pos..pos + 4].try_into().unwrap()) as usize;
+        let kind = &bytes[pos + 4..pos + 8];
+        let end = pos + 8 + len;
+        if end + 4 > bytes.len() {
+            return Err("PNG: chunk truncado".into());
+        }
+        let data = &bytes[pos + 8..end];
+        let crc = u32::from_be_bytes(bytes[end..end + 4].try_into().unwrap());
+        if crc32(&bytes[pos + 4..end]) != crc {
+            return Err(format!(
+                "PNG: CRC inválido en {}",
+                String::from_utf8_lossy(kind)
+            ));
+        }
+        match kind {
+            b"IHDR" if data.len() == 13 => {
+                let h = Header {
+                    width: u32::from_be_bytes(data[0..4].try_into().unwrap()) as usize,
+                    height: u32::from_be_bytes(data[4..8].try_into().unwrap()) as usize,
+                    depth: data[8],
+                    color: data[9],
+                    interlaced: data[12] == 1,
+                };
+                let valid = match h.color {
+                    0 => matches!(h.depth, 1 | 2 | 4 | 8 | 16),
+                    3 => matches!(h.depth, 1 | 2 | 4 | 8),
+                    2 | 4 | 6 => matches!(h.depth, 8 | 16),
+                    _ => false,
+                };
+                if !valid {
+                    return Err(format!(
+                        "PNG: tipo de color {} con {} bits no soportado",
+                        h.color, h.depth
+                    ));
+                }
+                header = Some(h);
+            }
+            b"PLTE" => {
+                palette = data
+                    .chunks_exact(3)
+                    .map(|c| [c[0], c[1], c[2], 255])
+                    .collect();
+            }
+            b"tRNS" => {
+                for (entry, &a) in palette.iter_mut().zip(data) {
+                    entry[3] = a;
+                }
+            }
+            b"IDAT" => idat.extend_from_slice(data),
+            b"IEND" => break,
+            _ => {}
+        }
+        pos = end + 4;
+    }
+    let h = header.ok_or("PNG: falta IHDR")?;
+    if h.color == 3 && palette.is_empty() {
+        return Err("PNG: imagen con paleta sin PLTE".into());
+    }
+    if h.width == 0 || h.height == 0 {
+        return Err("PNG: imagen vacía".into());
+    }
+    let passes: &[(usize, usize, usize, usize)] = if h.interlaced {
+        &ADAM7
+    } else {
+        &[(0, 0, 1, 1)]
+    };
+    // Ancho y alto de una pasada (una sola, de toda la imagen, sin entrelazado).
+    let pass_size = |&(x0, y0, dx, dy): &(usize, usize, usize, usize)| {
+        (
+            h.width.saturating_sub(x0).div_ceil(dx),
+            h.height.saturating_sub(y0).div_ceil(dy),
+        )
+    };
+    // El tamaño de IHDR limita lo que se descomprime y se compara con los datos antes de
+    // reservar la imagen.
+    let needed = passes
+        .iter()
+        .map(pass_size)
+        .filter(|&(pw, ph)| pw > 0 && ph > 0)
+        .try_fold(0usize, |acc, (pw, ph)| {
+            ph.checked_mul(h.row_bytes(pw) + 1)?.checked_add(acc)
+        })
+        .ok_or("PNG: imagen demasiado grande")?;
+    let raw = inflate::zlib_decompress(&idat, needed)?;
+    if raw.len() < needed {
+        return Err("PNG: datos de imagen truncados".into());
+    }
+
+    let mut pixels = vec![[0u8, 0, 0, 255]; h.width * h.height];
+    let mut offset = 0;
+    for pass in passes {
+        let (x0, y0, dx, dy) = *pass;
+        let (pw, ph) = pass_size(pass);
+        if pw == 0 || ph == 0 {
+            continue;
+        }
+        let rows = unfilter(&raw[offset.min(raw.len())..], &h, pw, ph)?;
+        offset += ph * (h.row_bytes(pw) + 1);
+        let stride = h.row_bytes(pw);
+        for py in 0..ph {
+            let row = &rows[py * stride..(py + 1) * stride];
+            for px in 0..pw {
+                let c = h.channels();
+                let s = |k: usize| sample(row, px * c + k, h.depth);
+                let rgba = match h.color {
+                    0 => [s(0), s(0), s(0), 255],
+                    2 => [s(0), s(1), s(2), 255],
+                    3 => *palette
+                        .get(index(row, px, h.depth))
+                        .ok_or("PNG: índice de paleta fuera de rango")?,
+                    4 => [s(0), s(0), s(0), s(1)],
+                    _ => [s(0), s(1), s(2), s(3)],
+                };
+                pixels[(y0 + py * dy) * h.width + x0 + px * dx] = rgba;
+            }
+        }
+    }
+    Ok((h.width, h.height, pixels))
+}
+
+fn chunk(out: &mut Vec<u8>, kind: &[u8; 4], data: &[u8]) {
+    out.extend((data.len() as u32).to_be_bytes());
+    let start = out.len();
+    out.extend(kind);
+    out.extend(data);
+    let crc = crc32(&out[start..]);
+    out.extend(crc.to_be_bytes());
+}
+
+// RGB de 8 bits. Cada fila usa el filtro con menor suma de valores absolutos
+// (la heurística que recomienda la especificación).
+pub fn encode(width: usize, height: usize, rgb: &[[u8; 3]]) -> Vec<u8> {
+    let stride = width * 3;
+    let bytes: Vec<u8> = rgb.iter().flatten().copied().collect();
+    let mut filtered = Vec::with_capacity(height * (stride + 1));
+    let mut candidate = vec![0u8; stride];
+    for y in 0..height {
+        let cur = &bytes[y * stride..(y + 1) * stride];
+        let prev = (y > 0).then(|| &bytes[(y - 1) * stride..y * stride]);
+        let mut best: Option<(u64, u8, Vec<u8>)> = None;
+        for filter in 0..5u8 {
+            for x in 0..stride {
+                let a = if x >= 3 { cur[x - 3] } else { 0 };
+                let b = prev.map_or(0, |p| p[x]);
+                let c = if x >= 3 {
+                    prev.map_or(0, |p| p[x - 3])
+                } else {
+                    0
+                };
+                candidate[x] = cur[x].wrapping_sub(match filter {
+                    0 => 0,
+                    1 => a,
+                    2 => b,
+                    3 => ((a as u16 + b as u16) / 2) as u8,
+                    _ => paeth(a, b, c),
+                });
+            }
+            let cost = candidate
+                .iter()
+                .map(|&v| (v as i8).unsigned_abs() as u64)
+                .sum();
+            if best.as_ref().is_none_or(|(c, _, _)| cost < *c) {
+                best = Some((cost, filter, candidate.clone()));
+            }
+        }
+        if let Some((_, filter, row)) = best {
+            filtered.push(filter);
+            filtered.extend(row);
+        }
+    }
+
+    let mut out = SIGNATURE.to_vec();
+    let mut ihdr = vec![];
+    ihdr.extend((width as u32).to_be_bytes());
+    ihdr.extend((height as u32).to_be_bytes());
+    ihdr.extend([8, 2, 0, 0, 0]); // 8 bits, RGB, deflate, filtro adaptativo, sin entrelazar
+    chunk(&mut out, b"IHDR", &ihdr);
+    chunk(&mut out, b"IDAT", &deflate::zlib_compress(&filtered));
+    chunk(&mut out, b"IEND", &[]);
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // PNG con `raw` (filas ya filtradas, por pasada si `interlaced`) y chunks extra antes de IDAT.
+    fn build(
+        w: u32,
+        h: u32,
+        depth: u8,
+        color: u8,
+        interlaced: bool,
+        extra: &[(&[u8; 4], &[u8])],
+        raw: &[u8],
+    ) -> Vec<u8> {
+        let mut out = SIGNATURE.to_vec();
+        let mut ihdr = vec![];
+        ihdr.extend(w.to_be_bytes());
+        ihdr.extend(h.to_be_bytes());
+        ihdr.extend([depth, color, 0, 0, interlaced as u8]);
+        chunk(&mut out, b"IHDR", &ihdr);
+        for (kind, data) in extra {
+            chunk(&mut out, kind, data);
+        }
+        chunk(&mut out, b"IDAT", &deflate::zlib_compress(raw));
+        chunk(&mut out, b"IEND", &[]);
+        out
+    }
+
+    fn image(w: usize, h: usize) -> Vec<[u8; 3]> {
+        (0..w * h)
+            .map(|i| {
+                [
+                    (i * 37) as u8,
+                    (i * 11 + 5) as u8,
+                    255u8.wrapping_sub((i * 3) as u8),
+                ]
+            })
+            .collect()
+    }
+
+    #[test]
+    fn encode_y_decode() {
+        for (w, h) in [(1, 1), (7, 5), (64, 3)] {
+            let rgb = image(w, h);
+            let (dw, dh, rgba) = decode(&encode(w, h, &rgb)).unwrap();
+            assert_eq!((dw, dh), (w, h));
+            let back: Vec<[u8; 3]> = rgba.iter().map(|p| [p[0], p[1], p[2]]).collect();
+            assert_eq!(back, rgb);
+            assert!(rgba.iter().all(|p| p[3] == 255));
+        }
+    }
+
+    #[test]
+    fn paleta_de_2_bits_con_transparencia() {
+        let plte = [255, 0, 0, 0, 255, 0, 0, 0, 255];
+        // Índices 0,1,2,1,0 en una fila de 5: 00 01 10 01 | 00 + relleno.
+        let raw = [0, 0b0001_1001, 0b0000_0000];
+        let png = build(
+            5,
+            1,
+            2,
+            3,
+            false,
+            &[(b"PLTE", &plte), (b"tRNS", &[128])],
+            &raw,
+        );
+        let (_, _, rgba) = decode(&png).unwrap();
+        assert_eq!(
+            rgba,
+            vec![
+                [255, 0, 0, 128],
+                [0, 255, 0, 255],
+                [0, 0, 255, 255],
+                [0, 255, 0, 255],
+                [255, 0, 0, 128]
+            ]
+        );
+    }
+
+    #[test]
+    fn gris_de_16_bits_con_filtros() {
+        // Dos filas de 2 píxeles: la primera sin filtro, la segunda con "arriba" (+0x10 al byte alto).
+        let raw = [0, 0x80, 0xff, 0x20, 0x00, 2, 0x10, 0, 0x10, 0];
+        let (_, _, rgba) = decode(&build(2, 2, 16, 0, false, &[], &raw)).unwrap();
+        let gray: Vec<u8> = rgba.iter().map(|p| p[0]).collect();
+        assert_eq!(gray, vec![0x80, 0x20, 0x90, 0x30]);
+    }
+
+    #[test]
+    fn entrelazado_adam7() {
+        let (w, h) = (5, 6);
+        let rgba: Vec<[u8; 4]> = (0..w * h)
+            .map(|i| [i as u8, 2 * i as u8, 100, 200 - i as u8])
+            .collect();
+        let mut raw = vec![];
+        for &(x0, y0, dx, dy) in &ADAM7 {
+            for y in (y0..h).step_by(dy) {
+                let xs: Vec<usize> = (x0..w).step_by(dx).collect();
+                if xs.is_empty() {
+                    continue;
+                }
+                raw.push(0);
+                for x in xs {
+                    raw.extend(rgba[y * w + x]);
+                }
+            }
+        }
+        let (_, _, back) = decode(&build(w as u32, h as u32, 8, 6, true, &[], &raw)).unwrap();
+        assert_eq!(back, rgba);
+    }
+
+    #[test]
+    fn archivos_corruptos_dan_error() {
+        let good = encode(7, 5, &image(7, 5));
+        assert!(decode(&good[1..]).is_err());
+        for cut in [8, 20, 33, 40, good.len() - 13] {
+            assert!(decode(&good[..cut]).is_err(), "cortado en {cut}");
+        }
+        let mut bad_crc = good.clone();
+        bad_crc[20] ^= 1;
+        assert!(decode(&bad_crc).is_err());
+        // Filtro 9 en la primera fila.
+        assert!(decode(&build(1, 1, 8, 2, false, &[], &[9, 1, 2, 3])).is_err());
+        // Paleta sin PLTE.
+        assert!(decode(&build(1, 1, 8, 3, false, &[], &[0, 0])).is_err());
+        // Profundidad inválida para RGB.
+        assert!(decode(&build(1, 1, 4, 2, false, &[], &[0, 0])).is_err());
+    }
+
+    #[test]
+    fn ihdr_enorme_con_pocos_datos_da_error() {
+        // 100000 x 100000 RGBA con una sola fila de datos: no debe reservar la imagen.
+        let png = build(100_000, 100_000, 8, 6, false, &[], &[0; 400_001]);
+        assert!(decode(&png).is_err());
+        let png = build(u32::MAX, u32::MAX, 16, 6, true, &[], &[0; 16]);
+        assert!(decode(&png).is_err());
+    }
+}
@@ -0,0 +1,257 @@
+// Lector de Radiance .hdr (RGBE): formato 32-bit_rle_rgbe, RLE nuevo y plano.
+use glm::{Vec3, vec3};
+use nalgebra_glm as glm;
+
+pub fn decode(bytes: &[u8]) -> Result<(usize, usize, Vec<Vec3>), String> {
+    let mut pos = 0;
+    let magic = read_line(bytes, &mut pos)?;
+    if !magic.starts_with("#?") {
+        return Err("no es un archivo Radiance HDR (falta #?RADIANCE)".into());
+    }
+    loop {
+        let line = read_line(bytes, &mut pos)?;
+        if line.is_empty() {
+            break;
+        }
+        if let Some(fmt) = line.strip_prefix("FORMAT=")
+            && fmt != "32-bit_rle_rgbe"
+        {
+            return Err(format!("formato HDR no soportado: {fmt}"));
+        }
+    }
+
+    let res = read_line(bytes, &mut pos)?;
+    let parts: Vec<&str> = res.split_whitespace().collect();
+    if parts.len() != 4 || parts[2] != "+X" {
+        return Err(format!("orientación HDR no soportada: {res}"));
+    }
+    let flip_y = match parts[0] {
+        "-Y" => false,
+        "+Y" => true,
+        _ => return Err(format!("orientación HDR no soportada: {res}")),
+    };
+    let h: usize = parts[1]
+        .parse()
+        .map_err(|_| format!("alto HDR inválido: {res}"))?;
+    let w: usize = parts[3]
+        .parse()
+        .map_err(|_| format!("ancho HDR inválido: {res}"))?;
+    if w == 0 || h == 0 {
+        return Err("imagen HDR vacía".into());
+    }
+
+    let len = w.checked_mul(h).ok_or("imagen HDR demasiado grande")?;
+    // Cada scanline ocupa al menos 4 bytes (8 si tiene más de un píxel) y cada grupo de 4
+    // da a lo sumo 2^24 píxeles con el RLE antiguo: una cabecera que pide más de lo que
+    // caben en los datos se rechaza antes de reservar la imagen.
+    let remaining = bytes.len() - pos;
+    let min_row = if w == 1 { 4 } else { 8 };
+    if h.checked_mul(min_row).is_none_or(|n| n > remaining)
+        || len > (remaining / 4).saturating_mul(1 << 24)
+    {
+        return Err("datos HDR truncados".into());
+    }
+    let mut pixels = vec![vec3(0.0, 0.0, 0.0); len];
+    let mut scanline = vec![[0u8; 4]; w];
+    for row in 0..h {
+        read_scanline(bytes, &mut pos, &mut scanline)?;
+        let y = if flip_y { h - 1 - row } else { row };
+        for (x, rgbe) in scanline.iter().enumerate() {
+            pixels[y * w + x] = rgbe_to_vec3(rgbe);
+        }
+    }
+    Ok((w, h, pixels))
+}
+
+fn read_line(bytes: &[u8], pos: &mut usize) -> Result<String, String> {
+    let start = *pos;
+    while *pos < bytes.len() && bytes[*pos] != b'\n' {
+        *pos += 1;
+    }
+    if *pos >= bytes.len() {
+        return Err("cabecera HDR truncada".into());
+    }
+    let line = String::from_utf8_lossy(&bytes[start..*pos])
+        .trim()
+        .to_string();
+    *pos += 1;
+    Ok(line)
+}
+
+fn rgbe_to_vec3(c: &[u8; 4]) -> Vec3 {
+    if c[3] == 0 {
+        return vec3(0.0, 0.0, 0.0);
+    }
+    let f = 2.0_f32.powi(c[3] as i32 - 136);
+    vec3(
+        (c[0] as f32 + 0.5) * f,
+        (c[1] as f32 + 0.5) * f,
+        (c[2] as f32 + 0.5) * f,
+    )
+}
+
+fn next(bytes: &[u8], pos: &mut usize) -> Result<u8, String> {
+    let b = *bytes.get(*pos).ok_or("datos HDR truncados")?;
+    *pos += 1;
+    Ok(b)
+}
+
+fn read_scanline(bytes: &[u8], pos: &mut usize, out: &mut [[u8; 4]]) -> Result<(), String> {
+    let w = out.len();
+    let peek = bytes.get(*pos..*pos + 4).ok_or("datos HDR truncados")?;
+    let is_new_rle =
+        (8..0x8000).contains(&w) && peek[0] == 2 && peek[1] == 2 && peek[2] & 0x80 == 0;
+    if !is_new_rle {
+        return read_flat_scanline(bytes, pos, out);
+    }
+    let len = ((peek[2] as usize) << 8) | peek[3] as usize;
+    if len != w {
+        return Err("ancho de scanline HDR inconsistente".into());
+    }
+    *pos += 4;
+    for ch in 0..4 {
+        let mut x = 0;
+        while x < w {
+            let count = next(bytes, pos)? as usize;
+            if count > 128 {
+                let run = count - 128;
+                if x + run > w {
+                    return Err("RLE HDR fuera de rango".into());
+                }
+                let v = next(bytes, pos)?;
+                for px in out[x..x + run].iter_mut() {
+                    px[ch] = v;
+                }
+                x += run;
+            } else {
+                if count == 0 || x + count > w {
+                    return Err("RLE HDR fuera de rango".into());
+                }
+                for px in out[x..x + count].iter_mut() {
+                    px[ch] = next(bytes, pos)?;
+                }
+                x += count;
+            }
+        }
+    }
+    Ok(())
+}
+
+// Scanline sin RLE o con el RLE antiguo (1,1,1,n repite el píxel anterior). Marcas
+// seguidas suman bytes más altos del largo: con 3 alcanza para cualquier ancho.
+fn read_flat_scanline(bytes: &[u8], pos: &mut usize, out: &mut [[u8; 4]]) -> Result<(), String> {
+    let mut x = 0;
+    let mut shift = 0;
+    while x < out.len() {
+        let px = [
+            next(bytes, pos)?,
+            next(bytes, pos)?,
+            next(bytes, pos)?,
+            next(bytes, pos)?,
+        ];
+        if px[0] == 1 && px[1] == 1 && px[2] == 1 {
+            if x == 0 {
+                return Err("RLE HDR antiguo sin píxel previo".into());
+            }
+            if shift >= 24 {
+                return Err("RLE HDR antiguo con demasiadas repeticiones seguidas".into());
+            }
+            let run = (px[3] as usize) << shift;
+            if x + run > out.len() {
+                return Err("RLE HDR fuera de rango".into());
+            }
+            let prev = out[x - 1];
+            for p in out[x..x + run].iter_mut() {
+                *p = prev;
+            }
+            x += run;
+            shift += 8;
+        } else {
+            out[x] = px;
+            x += 1;
+            shift = 0;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HEADER: &[u8] = b"#?RADIANCE\nFORMAT=32-bit_rle_rgbe\n\n";
+
+    fn file(resolution: &str, data: &[u8]) -> Vec<u8> {
+        let mut bytes = HEADER.to_vec();
+        bytes.extend_from_slice(resolution.as_bytes());
+        bytes.push(b'\n');
+        bytes.extend_from_slice(data);
+        bytes
+    }
+
+    #[test]
+    fn tres_tipos_de_scanline() {
+        let mut data = vec![];
+        // Fila 0, RLE nuevo: R repetido, G literal, B mitad y mitad, E repetido.
+        data.extend([2, 2, 0, 8]);
+        data.extend([128 + 8, 200]);
+        data.extend([8, 0, 10, 20, 30, 40, 50, 60, 70]);
+        data.extend([4, 1, 2, 3, 4, 128 + 4, 9]);
+        data.extend([128 + 8, 129]);
+        // Fila 1, plana.
+        for x in 0..8u8 {
+            data.extend([x * 20, 100, 255 - x, 130 + x]);
+        }
+        // Fila 2, RLE antiguo: un píxel y 7 repeticiones.
+        data.extend([50, 60, 70, 131, 1, 1, 1, 7]);
+
+        let (w, h, pixels) = decode(&file("-Y 3 +X 8", &data)).unwrap();
+        assert_eq!((w, h), (8, 3));
+        for x in 0..8 {
+            let b = if x < 4 { x as u8 + 1 } else { 9 };
+            let expected = [
+                rgbe_to_vec3(&[200, x as u8 * 10, b, 129]),
+                rgbe_to_vec3(&[x as u8 * 20, 100, 255 - x as u8, 130 + x as u8]),
+                rgbe_to_vec3(&[50, 60, 70, 131]),
+            ];
+            for (y, e) in expected.iter().enumerate() {
+                assert_eq!(pixels[y * 8 + x], *e, "píxel ({x}, {y})");
+            }
+        }
+    }
+
+    #[test]
+    fn mas_y_invierte_las_filas() {
+        let data = [10, 0, 0, 128, 20, 0, 0, 128];
+        let (_, _, pixels) = decode(&file("+Y 2 +X 1", &data)).unwrap();
+        assert_eq!(pixels[0], rgbe_to_vec3(&[20, 0, 0, 128]));
+        assert_eq!(pixels[1], rgbe_to_vec3(&[10, 0, 0, 128]));
+    }
+
+    #[test]
+    fn repeticiones_vacias_dan_error() {
+        let mut data = vec![5, 5, 5, 128];
+        for _ in 0..9 {
+            data.extend([1, 1, 1, 0]);
+        }
+        assert!(decode(&file("-Y 1 +X 4", &data)).is_err());
+    }
+
+    #[test]
+    fn datos_truncados_dan_error() {
+        let mut data = vec![];
+        data.extend([2, 2, 0, 8, 128 + 8, 200, 8, 0, 10]);
+        assert!(decode(&file("-Y 1 +X 8", &data)).is_err());
+        assert!(decode(&file("-Y 2 +X 1", &[1, 2, 3, 128])).is_err());
+        assert!(decode(b"#?RADIANCE\nFORMAT=32-bit_rle_rgbe\n").is_err());
+        assert!(decode(b"#?RADIANCE\nFORMAT=32-bit_rle_xyze\n\n-Y 1 +X 1\n\0\0\0\0").is_err());
+    }
+
+    #[test]
+    fn cabecera_enorme_sin_datos_da_error() {
+        assert!(decode(&file("-Y 100000 +X 100000", &[])).is_err());
+        assert!(decode(&file("-Y 1 +X 1000000000000", &[])).is_err());
+        // Pocos bytes no alcanzan para 8 filas aunque el ancho quepa.
+        assert!(decode(&file("-Y 8 +X 1", &[1, 2, 3, 4])).is_err());
+    }
+}
@@ -1,66 +1,199 @@
+use crate::color::Rgb;
+use crate::error::Error;
+use crate::formats::png;
+use glm::vec3;
+use nalgebra_glm as glm;
+#[cfg(feature = "raylib")]
 use raylib::prelude::*;
 
 pub struct FrameBuffer {
     pub width: i32,
     pub height: i32,
-    pub color_buffer: Image,
-    background_color: Color,
-    current_color: Color,
+    // Fila por fila, de arriba hacia abajo.
+    pub pixels: Vec<Rgb>,
+    background_color: Rgb,
+    current_color: Rgb,
 }
 
 impl FrameBuffer {
-    pub fn new(width: i32, height: i32, background_color: Color) -> Self {
-        let color_buffer = Image::gen_image_color(width, height, background_color);
+    pub fn new(width: i32, height: i32, background_color: Rgb) -> Self {
         FrameBuffer {
             width,
             height,
-            color_buffer,
+            pixels: vec![background_color; (width * height).max(0) as usize],
             background_color,
-            current_color: Color::WHITE,
+            current_color: Rgb::new(255, 255, 255),
         }
     }
 
-    pub fn set_background_color(&mut self, color: Color) {
+    pub fn set_background_color(&mut self, color: Rgb) {
         self.background_color = color;
-        self.color_buffer = Image::gen_image_color(self.width, self.height, color);
+        self.clear();
     }
 
     pub fn clear(&mut self) {
-        self.color_buffer = Image::gen_image_color(self.width, self.height, self.background_color);
+        self.pixels.fill(self.background_color);
     }
 
-    pub fn set_color(&mut self, color: Color) {
+    pub fn set_color(&mut self, color: Rgb) {
         self.current_color = color;
     }
 
     pub fn set_pixel(&mut self, x: i32, y: i32) {
+        self.set_pixel_color(x, y, self.current_color);
+    }
+
+    pub fn set_pixel_color(&mut self, x: i32, y: i32, color: Rgb) {
         if (0..self.width).contains(&x) && (0..self.height).contains(&y) {
-            self.color_buffer.draw_pixel(x, y, self.current_color);
+            self.pixels[(y * self.width + x) as usize] = color;
         }
     }
 
-    pub fn set_pixel_color(&mut self, x: i32, y: i32, color: Color) {
-        if (0..self.width).contains(&x) && (0..self.height).contains(&y) {
-            self.color_buffer.draw_pixel(x, y, color);
+    // El formato sale de la extensión: .png con el codificador propio (RGB de 8 bits) y el
+    // resto (.bmp, .tga, .jpg...) con raylib, si está.
+    pub fn render_to_file(&self, file_path: &str) -> Result<(), Error> {
+        if !file_path.to_lowercase().ends_with(".png") {
+            return self.export_other(file_path);
         }
+        let rgb: Vec<[u8; 3]> = self.pixels.iter().map(|c| [c.r, c.g, c.b]).collect();
+        let bytes = png::encode(self.width as usize, self.height as usize, &rgb);
+        std::fs::write(file_path, bytes).map_err(|e| Error::write(file_path, e))
     }
 
-    pub fn render_to_file(&self, file_path: &str) -> Result<(), String> {
-        self.color_buffer.export_image(file_path);
-        Ok(())
+    #[cfg(feature = "raylib")]
+    fn export_other(&self, file_path: &str) -> Result<(), Error> {
+        let c_path = std::ffi::CString::new(file_path)
+            .map_err(|_| Error::format(file_path, "ruta inválida"))?;
+        let mut image = Image::gen_image_color(self.width, self.height, Color::BLACK);
+        for (i, c) in self.pixels.iter().enumerate() {
+            let (x, y) = (i as i32 % self.width, i as i32 / self.width);
+            image.draw_pixel(x, y, Color::new(c.r, c.g, c.b, 255));
+        }
+        // `Image::export_image` descarta el resultado de raylib; se llama directo.
+        let ok = unsafe { raylib::ffi::ExportImage(*image, c_path.as_ptr()) };
+        if ok {
+            Ok(())
+        } else {
+            Err(Error::write(
+                file_path,
+                std::io::Error::other(
+                    "raylib no pudo exportar la imagen (¿carpeta o extensión válidas?)",
+                ),
+            ))
+        }
     }
 
-    pub fn present_scaled(&self, d: &mut RaylibDrawHandle, dst_x: i32, dst_y: i32, scale: i32) {
-        let scale = scale.max(1);
+    #[cfg(not(feature = "raylib"))]
+    fn export_other(&self, file_path: &str) -> Result<(), Error> {
+        Err(Error::format(file_path, "sin raylib solo se exporta a .png"))
+    }
 
-        let data = self.color_buffer.get_image_data(); // Box<[Color]>
+    // Bytes RGBA (R8G8B8A8), el formato de las texturas de raylib.
+    #[cfg(feature = "raylib")]
+    fn rgba_bytes(&self) -> Vec<u8> {
+        self.pixels
+            .iter()
+            .flat_map(|c| [c.r, c.g, c.b, 255])
+            .collect()
+    }
 
-        for y in 0..self.height {
-            let row_off = (y * self.width) as usize;
-            for x in 0..self.width {
-                let c = data[row_off + x as usize];
-                d.draw_rectangle(dst_x + x * scale, dst_y + y * scale, scale, scale, c);
+    // Reescala este FB al tamaño de `dst` con Catmull-Rom (`Upscale::Sharp`). Cercano y
+    // bilineal no pasan por acá: los hace la GPU al dibujar la textura de `Presenter`.
+    pub fn upscale_into(&self, dst: &mut FrameBuffer) {
+        let data = &self.pixels;
+        let (w, h) = (self.width, self.height);
+        let px = |x: i32, y: i32| {
+            data[(y.clamp(0, h - 1) * w + x.clamp(0, w - 1)) as usize].to_vec3()
+        };
+        // Pesos de Catmull-Rom para los 4 taps alrededor de `t` en [0, 1).
+        let cubic = |t: f32| {
+            let (t2, t3) = (t * t, t * t * t);
+            [
+                0.5 * (-t3 + 2.0 * t2 - t),
+                0.5 * (3.0 * t3 - 5.0 * t2 + 2.0),
+                0.5 * (-3.0 * t3 + 4.0 * t2 + t),
+                0.5 * (t3 - t2),
+            ]
+        };
+        let (sx, sy) = (w as f32 / dst.width as f32, h as f32 / dst.height as f32);
+        for y in 0..dst.height {
+            for x in 0..dst.width {
+                // Centro del píxel destino en coordenadas del origen.
+                let fx = (x as f32 + 0.5) * sx - 0.5;
+                let fy = (y as f32 + 0.5) * sy - 0.5;
+                let (ix, iy) = (fx.floor() as i32, fy.floor() as i32);
+                let (wxs, wys) = (cubic(fx - ix as f32), cubic(fy - iy as f32));
+                let mut acc = vec3(0.0, 0.0, 0.0);
+                for (j, wy) in wys.iter().enumerate() {
+                    for (i, wx) in wxs.iter().enumerate() {
+                        acc += px(ix + i as i32 - 1, iy + j as i32 - 1) * (wx * wy);
+                    }
+                }
+                dst.set_pixel_color(x, y, Rgb::from_vec3(&acc));
             }
         }
     }
 }
+
+// Muestra un FB en la ventana a través de una sola textura: se sube entera cada
+// cuadro con `update_texture` y se dibuja estirada con una llamada.
+#[cfg(feature = "raylib")]
+#[derive(Default)]
+pub struct Presenter {
+    texture: Option<Texture2D>,
+}
+
+#[cfg(feature = "raylib")]
+impl Presenter {
+    // Va antes de `begin_drawing`: subir la textura necesita el handle.
+    // `smooth` = filtro bilineal de la GPU; si no, vecino más cercano.
+    pub fn upload(
+        &mut self,
+        rl: &mut RaylibHandle,
+        thread: &RaylibThread,
+        fb: &FrameBuffer,
+        smooth: bool,
+    ) -> Result<(), Error> {
+        let same_size = self
+            .texture
+            .as_ref()
+            .is_some_and(|t| (t.width(), t.height()) == (fb.width, fb.height));
+        if !same_size {
+            // La textura se recrea solo cuando cambia el tamaño del FB.
+            let image = Image::gen_image_color(fb.width, fb.height, Color::BLACK);
+            let texture = rl
+                .load_texture_from_image(thread, &image)
+                .map_err(|e| Error::Gpu {
+                    msg: format!("textura de presentación: {e}"),
+                })?;
+            // Sin repetir: el bilineal no debe mezclar un borde con el opuesto.
+            texture.set_texture_wrap(thread, TextureWrap::TEXTURE_WRAP_CLAMP);
+            self.texture = Some(texture);
+        }
+        let Some(texture) = self.texture.as_mut() else {
+            return Ok(());
+        };
+        texture
+            .update_texture(&fb.rgba_bytes())
+            .map_err(|e| Error::Gpu {
+                msg: format!("textura de presentación: {e}"),
+            })?;
+        let filter = if smooth {
+            TextureFilter::TEXTURE_FILTER_BILINEAR
+        } else {
+            TextureFilter::TEXTURE_FILTER_POINT
+        };
+        texture.set_texture_filter(thread, filter);
+        Ok(())
+    }
+
+    // Dibuja lo último subido en el rectángulo (`x`, `y`, `w`, `h`).
+    pub fn draw(&self, d: &mut RaylibDrawHandle, x: i32, y: i32, w: i32, h: i32) {
+        let Some(texture) = self.texture.as_ref() else {
+            return;
+        };
+        let src = Rectangle::new(0.0, 0.0, texture.width() as f32, texture.height() as f32);
+        let dst = Rectangle::new(x as f32, y as f32, w as f32, h as f32);
+        d.draw_texture_pro(texture, src, dst, Vector2::zero(), 0.0, Color::WHITE);
+    }
+}
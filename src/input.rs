@@ -0,0 +1,234 @@
+use crate::error::Error;
+use raylib::consts::GamepadAxis::*;
+use raylib::consts::GamepadButton::*;
+use raylib::consts::KeyboardKey::{self, *};
+use std::collections::HashMap;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Action {
+    Forward,
+    Back,
+    Left,
+    Right,
+    Down,
+    Up,
+    Fast,
+    YawLeft,
+    YawRight,
+    PitchUp,
+    PitchDown,
+    ToggleMouse,
+    Jump,
+    ToggleWalk,
+}
+
+impl Action {
+    const ALL: [(Action, &'static str); 14] = [
+        (Action::Forward, "adelante"),
+        (Action::Back, "atras"),
+        (Action::Left, "izquierda"),
+        (Action::Right, "derecha"),
+        (Action::Down, "bajar"),
+        (Action::Up, "subir"),
+        (Action::Fast, "rapido"),
+        (Action::YawLeft, "girar_izquierda"),
+        (Action::YawRight, "girar_derecha"),
+        (Action::PitchUp, "mirar_arriba"),
+        (Action::PitchDown, "mirar_abajo"),
+        (Action::ToggleMouse, "capturar_mouse"),
+        (Action::Jump, "saltar"),
+        (Action::ToggleWalk, "caminar"),
+    ];
+
+    fn from_name(name: &str) -> Option<Action> {
+        Self::ALL.iter().find(|(_, n)| *n == name).map(|(a, _)| *a)
+    }
+}
+
+pub fn key_from_name(name: &str) -> Option<KeyboardKey> {
+    let up = name.trim().to_ascii_uppercase();
+    let letters = [
+        KEY_A, KEY_B, KEY_C, KEY_D, KEY_E, KEY_F, KEY_G, KEY_H, KEY_I, KEY_J, KEY_K, KEY_L, KEY_M,
+        KEY_N, KEY_O, KEY_P, KEY_Q, KEY_R, KEY_S, KEY_T, KEY_U, KEY_V, KEY_W, KEY_X, KEY_Y, KEY_Z,
+    ];
+    let digits = [
+        KEY_ZERO, KEY_ONE, KEY_TWO, KEY_THREE, KEY_FOUR, KEY_FIVE, KEY_SIX, KEY_SEVEN, KEY_EIGHT,
+        KEY_NINE,
+    ];
+    if up.len() == 1 {
+        let c = up.as_bytes()[0];
+        if c.is_ascii_uppercase() {
+            return Some(letters[(c - b'A') as usize]);
+        }
+        if c.is_ascii_digit() {
+            return Some(digits[(c - b'0') as usize]);
+        }
+    }
+    Some(match up.as_str() {
+        "SPACE" | "ESPACIO" => KEY_SPACE,
+        "TAB" => KEY_TAB,
+        "ENTER" => KEY_ENTER,
+        "UP" | "ARRIBA" => KEY_UP,
+        "DOWN" | "ABAJO" => KEY_DOWN,
+        "LEFT" | "IZQUIERDA" => KEY_LEFT,
+        "RIGHT" | "DERECHA" => KEY_RIGHT,
+        "SHIFT" | "LEFT_SHIFT" => KEY_LEFT_SHIFT,
+        "RIGHT_SHIFT" => KEY_RIGHT_SHIFT,
+        "CTRL" | "LEFT_CONTROL" => KEY_LEFT_CONTROL,
+        "RIGHT_CONTROL" => KEY_RIGHT_CONTROL,
+        "ALT" | "LEFT_ALT" => KEY_LEFT_ALT,
+        "PAGE_UP" => KEY_PAGE_UP,
+        "PAGE_DOWN" => KEY_PAGE_DOWN,
+        "HOME" => KEY_HOME,
+        "END" => KEY_END,
+        _ => return None,
+    })
+}
+
+// Velocidades en unidades por segundo / grados por segundo (independientes de los FPS).
+#[derive(Clone, Debug)]
+pub struct InputConfig {
+    pub bindings: HashMap<Action, Vec<KeyboardKey>>,
+    pub move_speed: f32,
+    pub fast_multiplier: f32,
+    pub turn_speed_deg: f32,
+    pub mouse_sensitivity: f32,
+    pub invert_y: bool,
+    pub gamepad: i32,
+    pub gamepad_deadzone: f32,
+    pub gamepad_look_speed_deg: f32,
+}
+
+impl Default for InputConfig {
+    fn default() -> Self {
+        let bindings = HashMap::from([
+            (Action::Forward, vec![KEY_W]),
+            (Action::Back, vec![KEY_S]),
+            (Action::Left, vec![KEY_A]),
+            (Action::Right, vec![KEY_D]),
+            (Action::Down, vec![KEY_Q]),
+            (Action::Up, vec![KEY_E]),
+            (Action::Fast, vec![KEY_LEFT_SHIFT]),
+            (Action::YawLeft, vec![KEY_LEFT]),
+            (Action::YawRight, vec![KEY_RIGHT]),
+            (Action::PitchUp, vec![KEY_UP]),
+            (Action::PitchDown, vec![KEY_DOWN]),
+            (Action::ToggleMouse, vec![KEY_TAB]),
+            (Action::Jump, vec![KEY_SPACE]),
+            (Action::ToggleWalk, vec![KEY_G]),
+        ]);
+        Self {
+            bindings,
+            move_speed: 4.8,
+            fast_multiplier: 2.5,
+            turn_speed_deg: 60.0,
+            mouse_sensitivity: 0.15,
+            invert_y: false,
+            gamepad: 0,
+            gamepad_deadzone: 0.15,
+            gamepad_look_speed_deg: 120.0,
+        }
+    }
+}
+
+impl InputConfig {
+    // Formato `clave = valor`, una por línea; `#` inicia un comentario.
+    // Las acciones aceptan varias teclas separadas por coma: `adelante = W, UP`.
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let mut cfg = Self::default();
+        for (i, raw) in text.lines().enumerate() {
+            let line = raw.split('#').next().unwrap_or("").trim();
+            if line.is_empty() {
+                continue;
+            }
+            let (key, value) = line
+                .split_once('=')
+                .ok_or_else(|| format!("línea {}: falta '='", i + 1))?;
+            let (key, value) = (key.trim(), value.trim());
+            let num = || {
+                value
+                    .parse::<f32>()
+                    .map_err(|_| format!("línea {}: número inválido '{value}'", i + 1))
+            };
+            match key {
+                "velocidad" => cfg.move_speed = num()?,
+                "multiplicador_rapido" => cfg.fast_multiplier = num()?,
+                "velocidad_giro" => cfg.turn_speed_deg = num()?,
+                "sensibilidad_mouse" => cfg.mouse_sensitivity = num()?,
+                "invertir_y" => cfg.invert_y = matches!(value, "true" | "si" | "1"),
+                "gamepad" => cfg.gamepad = num()? as i32,
+                "zona_muerta" => cfg.gamepad_deadzone = num()?,
+                "velocidad_mirada_gamepad" => cfg.gamepad_look_speed_deg = num()?,
+                _ => {
+                    let action = Action::from_name(key)
+                        .ok_or_else(|| format!("línea {}: clave desconocida '{key}'", i + 1))?;
+                    let keys = value
+                        .split(',')
+                        .map(|k| {
+                            key_from_name(k)
+                                .ok_or_else(|| format!("línea {}: tecla desconocida '{k}'", i + 1))
+                        })
+                        .collect::<Result<Vec<_>, _>>()?;
+                    cfg.bindings.insert(action, keys);
+                }
+            }
+        }
+        Ok(cfg)
+    }
+
+    pub fn load(path: &str) -> Result<Self, Error> {
+        let text = std::fs::read_to_string(path).map_err(|e| Error::read(path, e))?;
+        Self::parse(&text).map_err(|e| Error::scene(path, e))
+    }
+
+    pub fn down(&self, rl: &raylib::RaylibHandle, action: Action) -> bool {
+        self.bindings
+            .get(&action)
+            .is_some_and(|keys| keys.iter().any(|k| rl.is_key_down(*k)))
+    }
+
+    pub fn pressed(&self, rl: &raylib::RaylibHandle, action: Action) -> bool {
+        self.bindings
+            .get(&action)
+            .is_some_and(|keys| keys.iter().any(|k| rl.is_key_pressed(*k)))
+    }
+
+    fn axis(&self, rl: &raylib::RaylibHandle, axis: raylib::consts::GamepadAxis) -> f32 {
+        let v = rl.get_gamepad_axis_movement(self.gamepad, axis);
+        if v.abs() < self.gamepad_deadzone {
+            0.0
+        } else {
+            v
+        }
+    }
+
+    pub fn gamepad_state(&self, rl: &raylib::RaylibHandle) -> GamepadState {
+        if !rl.is_gamepad_available(self.gamepad) {
+            return GamepadState::default();
+        }
+        let mut vertical = 0.0;
+        if rl.is_gamepad_button_down(self.gamepad, GAMEPAD_BUTTON_RIGHT_TRIGGER_1) {
+            vertical += 1.0;
+        }
+        if rl.is_gamepad_button_down(self.gamepad, GAMEPAD_BUTTON_LEFT_TRIGGER_1) {
+            vertical -= 1.0;
+        }
+        GamepadState {
+            strafe: self.axis(rl, GAMEPAD_AXIS_LEFT_X),
+            forward: -self.axis(rl, GAMEPAD_AXIS_LEFT_Y),
+            look_x: self.axis(rl, GAMEPAD_AXIS_RIGHT_X),
+            look_y: -self.axis(rl, GAMEPAD_AXIS_RIGHT_Y),
+            vertical,
+        }
+    }
+}
+
+// Sticks con zona muerta aplicada; los bumpers suben/bajan.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct GamepadState {
+    pub strafe: f32,
+    pub forward: f32,
+    pub look_x: f32,
+    pub look_y: f32,
+    pub vertical: f32,
+}
@@ -0,0 +1,213 @@
+use crate::color::{Material, TexSlot};
+use crate::editor;
+use crate::intersect::Intersect;
+use crate::objects::Object;
+use crate::scene::Scene;
+use glm::Vec3;
+use nalgebra_glm as glm;
+use raylib::prelude::*;
+
+// Campos editables del material: (campo, etiqueta, paso de la rueda).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Field {
+    AlbedoR,
+    AlbedoG,
+    AlbedoB,
+    Specular,
+    Reflectivity,
+    Transparency,
+    Ior,
+    EmissionR,
+    EmissionG,
+    EmissionB,
+    EmissionStrength,
+    Ripple,
+}
+
+pub const FIELDS: [(Field, &str, f32); 12] = [
+    (Field::AlbedoR, "albedo r", 5.0),
+    (Field::AlbedoG, "albedo g", 5.0),
+    (Field::AlbedoB, "albedo b", 5.0),
+    (Field::Specular, "especular", 0.02),
+    (Field::Reflectivity, "reflectividad", 0.02),
+    (Field::Transparency, "transparencia", 0.02),
+    (Field::Ior, "ior", 0.01),
+    (Field::EmissionR, "emisión r", 5.0),
+    (Field::EmissionG, "emisión g", 5.0),
+    (Field::EmissionB, "emisión b", 5.0),
+    (Field::EmissionStrength, "intensidad emisión", 0.05),
+    (Field::Ripple, "ondas", 0.01),
+];
+
+impl Field {
+    pub fn get(self, m: &Material) -> f32 {
+        match self {
+            Field::AlbedoR => m.albedo_color.r as f32,
+            Field::AlbedoG => m.albedo_color.g as f32,
+            Field::AlbedoB => m.albedo_color.b as f32,
+            Field::Specular => m.specular,
+            Field::Reflectivity => m.reflectivity,
+            Field::Transparency => m.transparency,
+            Field::Ior => m.ior,
+            Field::EmissionR => m.emission.r as f32,
+            Field::EmissionG => m.emission.g as f32,
+            Field::EmissionB => m.emission.b as f32,
+            Field::EmissionStrength => m.emission_strength,
+            Field::Ripple => m.ripple,
+        }
+    }
+
+    // Recorta el valor al rango que tiene sentido para cada campo.
+    pub fn set(self, m: &mut Material, v: f32) {
+        let channel = v.round().clamp(0.0, 255.0) as u8;
+        match self {
+            Field::AlbedoR => m.albedo_color.r = channel,
+            Field::AlbedoG => m.albedo_color.g = channel,
+            Field::AlbedoB => m.albedo_color.b = channel,
+            Field::Specular => m.specular = v.clamp(0.0, 1.0),
+            Field::Reflectivity => m.reflectivity = v.clamp(0.0, 1.0),
+            Field::Transparency => m.transparency = v.clamp(0.0, 1.0),
+            Field::Ior => m.ior = v.clamp(1.0, 3.0),
+            Field::EmissionR => m.emission.r = channel,
+            Field::EmissionG => m.emission.g = channel,
+            Field::EmissionB => m.emission.b = channel,
+            Field::EmissionStrength => m.emission_strength = v.max(0.0),
+            Field::Ripple => m.ripple = v.max(0.0),
+        }
+    }
+}
+
+// Disposición del panel (píxeles de pantalla).
+pub const PANEL_W: i32 = 300;
+const LINE_H: i32 = 18;
+const FONT: i32 = 16;
+// Líneas de información antes de la lista de campos.
+const HEADER_LINES: i32 = 7;
+
+// Inspector: clic sobre un objeto muestra el impacto y su material, editable en vivo.
+pub struct Inspector {
+    pub enabled: bool,
+    // (índice en `objects`, impacto del clic)
+    selection: Option<(usize, Intersect)>,
+    pub field: usize,
+    // Esquina superior izquierda del panel en pantalla.
+    pub x: i32,
+    pub y: i32,
+}
+
+impl Inspector {
+    pub fn new(x: i32, y: i32) -> Self {
+        Self {
+            enabled: false,
+            selection: None,
+            field: 0,
+            x,
+            y,
+        }
+    }
+
+    pub fn select(&mut self, scene: &Scene, ro: &Vec3, rd: &Vec3) {
+        self.selection = editor::pick(scene, ro, rd);
+    }
+
+    pub fn clear(&mut self) {
+        self.selection = None;
+    }
+
+    // Fila de campo bajo el punto de pantalla `(mx, my)`, si cae sobre el panel.
+    pub fn field_at(&self, mx: i32, my: i32) -> Option<usize> {
+        self.selection.as_ref()?;
+        if mx < self.x || mx >= self.x + PANEL_W {
+            return None;
+        }
+        let row = (my - self.y - 4) / LINE_H - HEADER_LINES;
+        (my >= self.y && row >= 0 && (row as usize) < FIELDS.len()).then_some(row as usize)
+    }
+
+    pub fn contains(&self, mx: i32, my: i32) -> bool {
+        let rows = HEADER_LINES + FIELDS.len() as i32;
+        self.selection.is_some()
+            && mx >= self.x
+            && mx < self.x + PANEL_W
+            && my >= self.y
+            && my < self.y + rows * LINE_H + 8
+    }
+
+    // Cambia el campo elegido en `steps` pasos. Vale para todos los objetos que
+    // comparten el material (mismo nombre) y para su entrada en la paleta.
+    // Ojo: las pistas de animación vuelven a pisar emisión y ondas en cada cuadro.
+    pub fn adjust(&self, objects: &mut [Object], palette: &mut [Material], steps: f32) {
+        let Some((index, _)) = self.selection else {
+            return;
+        };
+        let Some(target) = objects.get(index) else {
+            return;
+        };
+        let (field, _, step) = FIELDS[self.field];
+        let name = target.material().name;
+        let value = field.get(target.material()) + step * steps;
+        for o in objects.iter_mut().filter(|o| o.material().name == name) {
+            field.set(o.material_mut(), value);
+        }
+        for m in palette.iter_mut().filter(|m| m.name == name) {
+            field.set(m, value);
+        }
+    }
+
+    pub fn draw(&self, d: &mut RaylibDrawHandle, objects: &[Object]) {
+        let Some((index, hit)) = &self.selection else {
+            return;
+        };
+        let Some(object) = objects.get(*index) else {
+            return;
+        };
+        let m = object.material();
+        let kind = match object {
+            Object::Cube(_) => "Cubo",
+            Object::Plane(_) => "Plano",
+        };
+        let tex = match &m.tex {
+            TexSlot::Some(t) if t.frames > 1 => {
+                format!("{}x{} ({} cuadros)", t.w, t.h, t.frames)
+            }
+            TexSlot::Some(t) => format!("{}x{}", t.w, t.h),
+            TexSlot::None => "sin textura".to_string(),
+        };
+        let (p, n) = (hit.point, hit.normal);
+        let mut lines = vec![
+            format!("{kind} #{index} — material '{}'", m.name),
+            format!("pos    {:.3} {:.3} {:.3}", p.x, p.y, p.z),
+            format!("normal {:.3} {:.3} {:.3}", n.x, n.y, n.z),
+            match hit.uv {
+                Some((u, v)) => format!("uv     {u:.3} {v:.3}"),
+                None => "uv     —".to_string(),
+            },
+            format!("distancia {:.3}", hit.distance),
+            format!("textura {tex}"),
+            "clic en campo + rueda = editar (Shift x10)".to_string(),
+        ];
+        for (field, label, _) in FIELDS.iter() {
+            lines.push(format!("{label:<20} {:.3}", field.get(m)));
+        }
+
+        let h = lines.len() as i32 * LINE_H + 8;
+        d.draw_rectangle(self.x, self.y, PANEL_W, h, Color::new(0, 0, 0, 180));
+        for (i, line) in lines.iter().enumerate() {
+            let row = i as i32 - HEADER_LINES;
+            let color = if row == self.field as i32 {
+                Color::YELLOW
+            } else if row >= 0 {
+                Color::WHITE
+            } else {
+                Color::LIGHTGRAY
+            };
+            d.draw_text(
+                line,
+                self.x + 6,
+                self.y + 4 + i as i32 * LINE_H,
+                FONT,
+                color,
+            );
+        }
+    }
+}
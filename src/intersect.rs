@@ -43,4 +43,9 @@ impl Intersect {
 
 pub trait RayIntersect {
     fn ray_intersect(&self, ro: &Vec3, rd: &Vec3) -> Intersect;
+
+    // `time` en [0, 1] dentro del obturador; los objetos estáticos lo ignoran.
+    fn ray_intersect_at(&self, ro: &Vec3, rd: &Vec3, _time: f32) -> Intersect {
+        self.ray_intersect(ro, rd)
+    }
 }
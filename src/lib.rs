@@ -0,0 +1,41 @@
+// Trazador de rayos del diorama: cámara, materiales, objetos, luces, render y carga de
+// texturas y escenas. El visor interactivo (`src/main.rs`, feature `raylib`) y el render
+// offline (`src/bin/offline.rs`) son binarios que la usan; el diorama y la lectura de
+// argumentos (`src/diorama.rs`, `src/cli.rs`) los comparten como módulos propios.
+pub mod animation;
+pub mod aov;
+pub mod bvh;
+pub mod camera;
+pub mod camera_path;
+pub mod color;
+pub mod cubemap;
+pub mod denoise;
+pub mod editor;
+pub mod environment;
+pub mod error;
+pub mod formats;
+pub mod framebuffer;
+#[cfg(feature = "raylib")]
+pub mod input;
+#[cfg(feature = "raylib")]
+pub mod inspector;
+pub mod intersect;
+pub mod objects;
+pub mod renderer;
+pub mod resolution;
+pub mod sampling;
+pub mod scene;
+pub mod sky;
+pub mod temporal;
+pub mod texture;
+pub mod walk;
+
+pub use camera::Camera;
+pub use color::{Material, Rgb, TexSlot};
+pub use environment::Environment;
+pub use error::Error;
+pub use framebuffer::FrameBuffer;
+pub use objects::{Cube, Object, Plane};
+pub use renderer::{DirectionalLight, PointLight, RenderSettings};
+pub use scene::{Fog, Scene, SceneBuilder};
+pub use texture::Texture;
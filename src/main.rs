@@ -1,21 +1,24 @@
-use glm::vec3;
-use nalgebra_glm as glm;
+use proyect2_raytracing::aov;
+use proyect2_raytracing::camera_path::CameraPath;
+use proyect2_raytracing::color::Rgb;
+use proyect2_raytracing::denoise::Denoiser;
+use proyect2_raytracing::editor::{self, Editor};
+use proyect2_raytracing::framebuffer::{FrameBuffer, Presenter};
+use proyect2_raytracing::input::{Action, InputConfig};
+use proyect2_raytracing::inspector::{self, Inspector};
+use proyect2_raytracing::renderer::{self, DirectionalLight, RenderMode};
+use proyect2_raytracing::resolution::{DynamicResolution, Upscale};
+use proyect2_raytracing::scene::Scene;
+use proyect2_raytracing::temporal::Temporal;
+use proyect2_raytracing::walk::Walker;
 use raylib::prelude::*;
-use std::sync::Arc;
 
-mod camera;
-mod color;
-mod framebuffer;
-mod intersect;
-mod objects;
-mod renderer;
-mod texture;
+mod cli;
+mod diorama;
 
-use camera::Camera;
-use color::{Material, Rgb, TexSlot};
-use framebuffer::FrameBuffer;
-use objects::{Cube, Object, Plane};
+use cli::arg_value;
 
+// Visor interactivo. El render offline de AOV y recorridos está en `src/bin/offline.rs`.
 fn main() {
     let width: i32 = 960;
     let height: i32 = 540;
@@ -26,317 +29,535 @@ fn main() {
         .build();
 
     // ===================== Cámara (vista lateral) =====================
-    let mut cam = Camera::new(
-        vec3(0.0, 1.8, 2.8), // pos
-        vec3(0.0, 3.6, -4.8), // target
-        vec3(0.0, 1.0, 10.0), // up
-        60.0,
-        width as f32 / height as f32,
-    );
+    let mut cam = diorama::camera(width as f32 / height as f32);
+    cli::camera_options(&mut cam);
 
-    // ===================== Texturas =====================
-    let tex_brick = Arc::new(texture::Texture::from_file("assets/texture.png"));
-    let tex_ground = Arc::new(texture::Texture::from_file("assets/ground.png"));
-    let tex_glowstone = Arc::new(texture::Texture::from_file("assets/glowstone.png"));
-    let tex_bark = Arc::new(texture::Texture::from_file("assets/bark.png"));
-    let tex_leaves = Arc::new(texture::Texture::from_file("assets/leaves.png"));
-    let skybox = texture::Texture::from_file_maybe("assets/skybox.png");
-
-    // ===================== Materiales =====================
-    let mat_brick = color::Material {
-        tex: TexSlot::Some(tex_brick.clone()),
-        albedo_color: Rgb::new(200, 170, 120),
-        specular: 0.12,
-        reflectivity: 0.08,
-        transparency: 0.0,
-        ior: 1.0,
-        emission: Rgb::new(0, 0, 0),
-        emission_strength: 0.0,
-    };
-    let mat_ground = color::Material {
-        tex: TexSlot::Some(tex_ground.clone()),
-        albedo_color: Rgb::new(255, 255, 255),
-        specular: 0.05,
-        reflectivity: 0.02,
-        transparency: 0.0,
-        ior: 1.0,
-        emission: Rgb::new(0, 0, 0),
-        emission_strength: 0.0,
-    };
-    let mat_glowstone = color::Material {
-        tex: TexSlot::Some(tex_glowstone.clone()),
-        albedo_color: Rgb::new(255, 255, 200),
-        specular: 0.30,
-        reflectivity: 0.10,
-        transparency: 0.0,
-        ior: 1.0,
-        emission: Rgb::new(255, 240, 200),
-        emission_strength: 0.5, // brilla
-    };
-    let mat_metal = Material::solid_with(Rgb::new(180, 180, 190), 0.75, 0.65);
-    let mat_vidrio = color::Material {
-        tex: TexSlot::None,
-        albedo_color: Rgb::new(200, 220, 235),
-        specular: 0.25,
-        reflectivity: 0.08,
-        transparency: 0.92,
-        ior: 1.52,
-        emission: Rgb::new(0, 0, 0),
-        emission_strength: 0.0,
+    // ===================== Controles =====================
+    // `--input archivo` con las teclas y velocidades; por defecto `input.cfg` si existe.
+    let input_path = arg_value("--input").unwrap_or_else(|| "input.cfg".to_string());
+    let input = if std::path::Path::new(&input_path).exists() {
+        InputConfig::load(&input_path).unwrap_or_else(|e| {
+            eprintln!("No pude leer los controles ({e}); uso los de por defecto");
+            InputConfig::default()
+        })
+    } else {
+        InputConfig::default()
     };
-    let mat_agua = color::Material {
-        tex: TexSlot::None,
-        albedo_color: Rgb::new(110, 140, 180),
-        specular: 0.20,
-        reflectivity: 0.08,
-        transparency: 0.80,
-        ior: 1.33,
-        emission: Rgb::new(0, 0, 0),
-        emission_strength: 0.0,
-    };
-    let mat_tronco = color::Material {
-        tex: TexSlot::Some(tex_bark.clone()),
-        albedo_color: Rgb::new(255, 255, 255),
-        specular: 0.06,
-        reflectivity: 0.03,
-        transparency: 0.0,
-        ior: 1.0,
-        emission: Rgb::new(0, 0, 0),
-        emission_strength: 0.0,
-    };
-    let mat_hojas = color::Material {
-        tex: TexSlot::Some(tex_leaves.clone()),
-        albedo_color: Rgb::new(255, 255, 255),
-        specular: 0.04,
-        reflectivity: 0.02,
-        transparency: 0.0,
-        ior: 1.0,
-        emission: Rgb::new(0, 0, 0),
-        emission_strength: 0.0,
-    };
-
-    // ===================== Framebuffer (half res + scale) =====================
-    let fb_w = width / 2;
-    let fb_h = height / 2;
-    let scale = 2; // pinta el FB a 2x
-    let mut fb = FrameBuffer::new(fb_w, fb_h, Color::BLACK);
-    cam.set_aspect(fb_w as f32 / fb_h as f32);
-
-    // ===================== Escena estática (objetos + luces) =====================
-    let mut objs: Vec<Object> = vec![];
 
-    // Parámetros “voxel”
-    let b: f32 = 0.4; // tamaño de cubo
-    let nx: i32 = 10; // ancho casa
-    let nz: i32 = 8; // profundidad casa
-    let ny: i32 = 5; // altura paredes
+    // Modo caminata (G por defecto): gravedad, colisiones, escalones y salto.
+    let mut walker = Walker::default();
 
-    // Referencias
-    let world_floor_y = -0.6; // piso del mundo
-    let base_y = world_floor_y + b * 1.5; // casa subida 1 bloque
-    let house_center = vec3(0.0, 0.0, -5.0);
-
-    let halfx = (nx as f32 - 1.0) * 0.5;
-    let halfz = (nz as f32 - 1.0) * 0.5;
-    let pos = |ix: i32, iy: i32, iz: i32| -> glm::Vec3 {
-        house_center
-            + vec3(
-                (ix as f32 - halfx) * b,
-                base_y + (iy as f32) * b,
-                (iz as f32 - halfz) * b,
-            )
+    // ===================== Recorrido de cámara =====================
+    // `--path archivo` guarda/carga los keyframes (por defecto `camera_path.txt`).
+    let path_file = arg_value("--path").unwrap_or_else(|| "camera_path.txt".to_string());
+    let mut cam_path = if std::path::Path::new(&path_file).exists() {
+        CameraPath::load(&path_file).unwrap_or_else(|e| {
+            eprintln!("No pude leer el recorrido ({e}); empiezo uno vacío");
+            CameraPath::default()
+        })
+    } else {
+        CameraPath::default()
     };
+    let key_spacing = 1.5; // segundos entre keyframes nuevos
+    let mut playing = false;
+    let mut play_time = 0.0;
 
-    // Plataforma grande
-    let pad_plat = 6;
-    let nxp = nx + pad_plat * 2;
-    let nzp = nz + pad_plat * 2;
-    let plat_center = house_center;
-    let plat_y = world_floor_y + b * 0.5; // toca el mundo
-    let halfx_p = (nxp as f32 - 1.0) * 0.5;
-    let halfz_p = (nzp as f32 - 1.0) * 0.5;
+    // ===================== Texturas y materiales =====================
+    let mut palette = diorama::palette(cli::strict_textures()).unwrap_or_else(|e| {
+        eprintln!("{e}");
+        std::process::exit(1);
+    });
 
-    for ix in 0..nxp {
-        for iz in 0..nzp {
-            let p =
-                plat_center + vec3((ix as f32 - halfx_p) * b, plat_y, (iz as f32 - halfz_p) * b);
-            let tile = Cube::from_center_size_rot(p, b, 0.0, 0.0, 0.0, mat_ground.clone());
-            objs.push(Object::Cube(tile));
+    // ===================== Framebuffer (resolución dinámica) =====================
+    // La escala del FB respecto de la ventana se ajusta cada cuadro para sostener
+    // `--fps-objetivo` (30), entre `--escala-min` (0.25) y `--escala-max` (1.0);
+    // U la fija a la mitad como antes. `--escalado cercano|bilineal|nitido` elige el
+    // filtro para llevarlo a la ventana (X lo cambia en vivo).
+    let mut dynres = DynamicResolution::default();
+    if let Some(v) = arg_value("--fps-objetivo").and_then(|v| v.parse::<f32>().ok()) {
+        dynres.target_fps = v.max(1.0);
+    }
+    if let Some(v) = arg_value("--escala-min").and_then(|v| v.parse::<f32>().ok()) {
+        dynres.min_scale = v.clamp(0.05, 1.0);
+    }
+    if let Some(v) = arg_value("--escala-max").and_then(|v| v.parse::<f32>().ok()) {
+        dynres.max_scale = v.clamp(dynres.min_scale, 1.0);
+    }
+    dynres.scale = dynres.scale.clamp(dynres.min_scale, dynres.max_scale);
+    let mut upscale = Upscale::Bilinear;
+    if let Some(name) = arg_value("--escalado") {
+        match Upscale::from_name(&name) {
+            Some(u) => upscale = u,
+            None => eprintln!("Filtro de escalado desconocido '{name}'; uso bilineal"),
         }
     }
+    let (fb_w, fb_h) = dynres.size(width, height);
+    let mut fb = FrameBuffer::new(fb_w, fb_h, Rgb::new(0, 0, 0));
+    // FB a tamaño de ventana para el escalado nítido (se hace en la CPU).
+    let mut screen = FrameBuffer::new(width, height, Rgb::new(0, 0, 0));
+    // Textura con la que se muestra el FB en la ventana.
+    let mut presenter = Presenter::default();
+    cam.set_aspect(fb_w as f32 / fb_h as f32);
+    // Cámara quieta (y sin animación) durante este tiempo: un cuadro a resolución completa
+    // que se sigue mostrando hasta que algo cambie.
+    let still_delay = 0.3;
+    let mut still_time = 0.0;
+    let mut last_pose = (cam.pos, cam.yaw, cam.pitch);
+    let mut full_res_held = false;
+    let mut last_frame_dynamic = false;
 
-    // Casa (aperturas)
-    let door_w_blocks = 2;
-    let door_h_blocks = 3;
-    let door_ix_center = nx / 2;
-    let front_z = nz - 1;
+    // ===================== Escena (objetos, luces y cielo) =====================
+    // `--sky` acepta un equirectangular (.png/.jpg/.hdr/.exr...), una cruz de cubemap, una carpeta
+    // con las 6 caras o `fisico`; `--niebla` agrega niebla.
+    let diorama_objects = diorama::objects(&palette);
+    let (scene_file, objs) = cli::scene(&mut palette, diorama_objects);
+    // Luces: una por bloque emisivo (glowstone)
+    let lights = diorama::lights(&objs);
+    // Animación (T la activa en vivo; apagada de entrada, como la vista quieta de siempre).
+    let mut timeline = diorama::timeline(&objs, &lights);
+    let mut scene = Scene::builder()
+        .with_objects(objs)
+        .with_lights(lights)
+        .with_environment(diorama::sky(&cli::sky_path()))
+        .build();
+    cli::fog_options(&mut scene.fog);
+    let mut anim_time = 0.0;
+    let mut animate = false;
 
-    let win_w_blocks = 2;
-    let win_h_blocks = 2;
-    let win_y_center = 3;
+    // ===================== Editor de bloques =====================
+    // F3 lo activa: clic izq. quita, clic der. pone, rueda elige material,
+    // Ctrl+Z deshace y Ctrl+S guarda la escena.
+    let mut editor = Editor::new(palette);
+    // F4: inspector (clic = objeto bajo el cursor; clic en un campo + rueda lo edita).
+    let mut inspector = Inspector::new(width - inspector::PANEL_W - 10, 10);
 
-    // Paredes de ladrillo con huecos
-    for iy in 0..ny {
-        for ix in 0..nx {
-            for iz in 0..nz {
-                let is_front = iz == front_z;
-                let is_back = iz == 0;
-                let is_left = ix == 0;
-                let is_right = ix == nx - 1;
-                let on_perimeter = is_front || is_back || is_left || is_right;
-                if !on_perimeter {
-                    continue;
-                }
+    // ===================== Ajustes de render =====================
+    scene.settings.ao.samples = 4; // barato para la vista interactiva
+    cli::render_options(&mut scene.settings);
+    // Denoiser à-trous guiado por albedo/normal/profundidad (N lo activa, F6/F7 pasadas,
+    // F8/F9 tolerancia de color); ver `cli::denoiser_options`.
+    let mut denoiser = Denoiser::default();
+    cli::denoiser_options(&mut denoiser);
+    let mut features = aov::AovBuffer::new(fb_w, fb_h);
+    // Reproyección temporal del cuadro anterior con jitter subpíxel (R para comparar).
+    let mut temporal = Temporal::default();
 
-                // puerta
-                if is_front {
-                    let in_door_x = (ix >= door_ix_center - (door_w_blocks / 2))
-                        && (ix <= door_ix_center + (door_w_blocks / 2) - 1);
-                    let in_door_y = iy < door_h_blocks;
-                    if in_door_x && in_door_y {
-                        continue;
+    // ===================== LOOP INTERACTIVO =====================
+    while !rl.window_should_close() {
+        // Recorrido: K añade la pose actual, J quita la última, H reproduce, F2 guarda.
+        if rl.is_key_pressed(KeyboardKey::KEY_K) {
+            cam_path.push_camera(&cam, key_spacing);
+        }
+        if rl.is_key_pressed(KeyboardKey::KEY_J) {
+            cam_path.pop();
+        }
+        if rl.is_key_pressed(KeyboardKey::KEY_H) && !cam_path.keys.is_empty() {
+            playing = !playing;
+            play_time = 0.0;
+        }
+        if rl.is_key_pressed(KeyboardKey::KEY_F2) {
+            match cam_path.save(&path_file) {
+                Ok(()) => println!("Recorrido guardado en {path_file}"),
+                Err(e) => eprintln!("No pude guardar el recorrido ({e})"),
+            }
+        }
+        // En el editor, con Ctrl apretado las teclas son atajos (Ctrl+S, Ctrl+Z) y no mueven.
+        let ctrl = rl.is_key_down(KeyboardKey::KEY_LEFT_CONTROL)
+            || rl.is_key_down(KeyboardKey::KEY_RIGHT_CONTROL);
+        let shortcut = editor.enabled && ctrl;
+        if playing {
+            play_time += rl.get_frame_time();
+            cam_path.apply(&mut cam, play_time);
+            playing = play_time < cam_path.duration();
+        } else {
+            if input.pressed(&rl, Action::ToggleWalk) {
+                walker.toggle(&cam);
+            }
+            if walker.enabled {
+                cam.look_from_input(&mut rl, &input);
+                let wish = if shortcut {
+                    nalgebra_glm::Vec3::zeros()
+                } else {
+                    cam.move_input(&rl, &input)
+                };
+                let jump = !shortcut && input.down(&rl, Action::Jump);
+                walker.update(&mut cam, &wish, jump, rl.get_frame_time(), scene.objects());
+            } else if shortcut {
+                cam.look_from_input(&mut rl, &input);
+            } else {
+                cam.update_from_input(&mut rl, &input);
+            }
+        }
+
+        if rl.is_key_pressed(KeyboardKey::KEY_F3) {
+            editor.enabled = !editor.enabled;
+            inspector.enabled = false;
+        }
+        if rl.is_key_pressed(KeyboardKey::KEY_F4) {
+            inspector.enabled = !inspector.enabled;
+            inspector.clear();
+            editor.enabled = false;
+        }
+        // Píxel del FB bajo el cursor; con el mouse capturado se apunta con el centro.
+        let mouse = rl.get_mouse_position();
+        let (fb_w, fb_h) = (fb.width, fb.height);
+        let (px, py) = if rl.is_cursor_hidden() {
+            (fb_w / 2, fb_h / 2)
+        } else {
+            (
+                (mouse.x * fb_w as f32 / width as f32) as i32,
+                (mouse.y * fb_h as f32 / height as f32) as i32,
+            )
+        };
+        if editor.enabled {
+            let wheel = rl.get_mouse_wheel_move();
+            if wheel != 0.0 {
+                editor.cycle(if wheel > 0.0 { -1 } else { 1 });
+            }
+            if ctrl && rl.is_key_pressed(KeyboardKey::KEY_Z) {
+                let (objects, lights) = scene.edit();
+                editor.undo(objects, lights, &mut timeline);
+            }
+            if ctrl && rl.is_key_pressed(KeyboardKey::KEY_S) {
+                match editor::save_scene(&scene_file, scene.objects(), &timeline, &editor.palette) {
+                    Ok(()) => println!("Escena guardada en {scene_file}"),
+                    Err(e) => eprintln!("No pude guardar la escena ({e})"),
+                }
+            }
+            let remove = rl.is_mouse_button_pressed(MouseButton::MOUSE_BUTTON_LEFT);
+            let place = rl.is_mouse_button_pressed(MouseButton::MOUSE_BUTTON_RIGHT);
+            if remove || place {
+                let hit = cam
+                    .primary_ray(px, py, fb_w, fb_h)
+                    .and_then(|(ro, rd)| editor::pick(&scene, &ro, &rd));
+                if let Some((index, hit)) = hit {
+                    let (objects, lights) = scene.edit();
+                    if remove {
+                        editor.remove(objects, lights, &mut timeline, index);
+                    } else {
+                        editor.place(objects, lights, &mut timeline, index, &hit);
                     }
                 }
-
-                // ventanas en izq/der
-                let win_z0 = nz / 2 - win_w_blocks / 2;
-                let win_z1 = win_z0 + win_w_blocks - 1;
-                let win_y0 = win_y_center - win_h_blocks / 2;
-                let win_y1 = win_y0 + win_h_blocks - 1;
-                if is_left || is_right {
-                    if iy >= win_y0 && iy <= win_y1 && iz >= win_z0 && iz <= win_z1 {
-                        continue;
+            }
+        }
+        if inspector.enabled {
+            let (mx, my) = (mouse.x as i32, mouse.y as i32);
+            let on_panel = !rl.is_cursor_hidden() && inspector.contains(mx, my);
+            if rl.is_mouse_button_pressed(MouseButton::MOUSE_BUTTON_LEFT) {
+                if on_panel {
+                    if let Some(field) = inspector.field_at(mx, my) {
+                        inspector.field = field;
                     }
+                } else if let Some((ro, rd)) = cam.primary_ray(px, py, fb_w, fb_h) {
+                    inspector.select(&scene, &ro, &rd);
                 }
-
-                let c = Cube::from_center_size_rot(
-                    pos(ix, iy, iz),
-                    b,
-                    0.0,
-                    0.0,
-                    0.0,
-                    mat_brick.clone(),
-                );
-                objs.push(Object::Cube(c));
+            }
+            let wheel = rl.get_mouse_wheel_move();
+            if wheel != 0.0 {
+                let fast = rl.is_key_down(KeyboardKey::KEY_LEFT_SHIFT);
+                let steps = wheel.signum() * if fast { 10.0 } else { 1.0 };
+                inspector.adjust(scene.objects_mut(), &mut editor.palette, steps);
             }
         }
-    }
 
-    // Dintel
-    for ix in (door_ix_center - (door_w_blocks / 2))..=(door_ix_center + (door_w_blocks / 2) - 1) {
-        let iy = door_h_blocks;
-        let c =
-            Cube::from_center_size_rot(pos(ix, iy, front_z), b, 0.0, 0.0, 0.0, mat_brick.clone());
-        objs.push(Object::Cube(c));
-    }
-
-    // Ventanas
-    for dz in 0..win_w_blocks {
-        for dy in 0..win_h_blocks {
-            let iz = (nz / 2 - win_w_blocks / 2) + dz;
-            let iy = (win_y_center - win_h_blocks / 2) + dy;
-
-            let c_l = Cube::from_center_size_rot(
-                pos(0, iy, iz),
-                b * 0.9,
-                0.0,
-                0.0,
-                0.0,
-                mat_vidrio.clone(),
-            );
-            let c_r = Cube::from_center_size_rot(
-                pos(nx - 1, iy, iz),
-                b * 0.9,
-                0.0,
-                0.0,
-                0.0,
-                mat_vidrio.clone(),
-            );
-            objs.push(Object::Cube(c_l));
-            objs.push(Object::Cube(c_r));
+        let save_snap = rl.is_key_pressed(KeyboardKey::KEY_P);
+        let save_aov = rl.is_key_pressed(KeyboardKey::KEY_F5);
+        if rl.is_key_pressed(KeyboardKey::KEY_O) {
+            scene.settings.ao.enabled = !scene.settings.ao.enabled;
         }
-    }
-
-    // Techo con glowstone + luces
-    let mut lights: Vec<renderer::PointLight> = vec![];
-    let roof_y = ny;
-    for ix in 0..nx {
-        for iz in 0..nz {
-            let p = pos(ix, roof_y, iz);
-            let c = Cube::from_center_size_rot(p, b, 0.0, 0.0, 0.0, mat_glowstone.clone());
-            objs.push(Object::Cube(c));
-            lights.push(renderer::PointLight {
-                pos: p,
-                color: Rgb::new(255, 240, 200),
-                intensity: 6.0,
-            });
+        if rl.is_key_pressed(KeyboardKey::KEY_M) {
+            scene.settings.mode = match scene.settings.mode {
+                RenderMode::AmbientOcclusion => RenderMode::Shaded,
+                _ => RenderMode::AmbientOcclusion,
+            };
         }
-    }
-
-    // Árbol al frente izquierda
-    let tree_base_x = plat_center.x - (halfx_p - 2.0) * b;
-    let tree_base_z = plat_center.z + (halfz_p - 2.5) * b;
-    let tree_base_y = plat_y + b;
-
-    // Tronco
-    let trunk_h = 4;
-    for i in 0..trunk_h {
-        let p = vec3(tree_base_x, tree_base_y + (i as f32) * b, tree_base_z);
-        let trunk_block = Cube::from_center_size_rot(p, b, 0.0, 0.0, 0.0, mat_tronco.clone());
-        objs.push(Object::Cube(trunk_block));
-    }
-    // Copa
-    let crown_center_y = tree_base_y + (trunk_h as f32) * b;
-    for dx in -1..=1 {
-        for dy in -1..=1 {
-            for dz in -1..=1 {
-                let p = vec3(
-                    tree_base_x + (dx as f32) * b,
-                    crown_center_y + (dy as f32) * b,
-                    tree_base_z + (dz as f32) * b,
-                );
-                let leaf = Cube::from_center_size_rot(p, b, 0.0, 0.0, 0.0, mat_hojas.clone());
-                objs.push(Object::Cube(leaf));
+        // 1..9, 0: vista sombreada, AO y las de depuración (ver `RenderMode::ALL`).
+        let number_keys = [
+            KeyboardKey::KEY_ONE,
+            KeyboardKey::KEY_TWO,
+            KeyboardKey::KEY_THREE,
+            KeyboardKey::KEY_FOUR,
+            KeyboardKey::KEY_FIVE,
+            KeyboardKey::KEY_SIX,
+            KeyboardKey::KEY_SEVEN,
+            KeyboardKey::KEY_EIGHT,
+            KeyboardKey::KEY_NINE,
+            KeyboardKey::KEY_ZERO,
+        ];
+        for (key, (mode, _)) in number_keys.iter().zip(RenderMode::ALL) {
+            if rl.is_key_pressed(*key) {
+                scene.settings.mode = mode;
             }
         }
-    }
+        if rl.is_key_pressed(KeyboardKey::KEY_I) {
+            scene.settings.ibl = !scene.settings.ibl;
+        }
+        if rl.is_key_pressed(KeyboardKey::KEY_V) {
+            cam.projection = cam.projection.next();
+        }
+        if rl.is_key_pressed(KeyboardKey::KEY_B) {
+            cam.stereo = cam.stereo.next();
+        }
+        if rl.is_key_pressed(KeyboardKey::KEY_T) {
+            animate = !animate;
+        }
+        if rl.is_key_pressed(KeyboardKey::KEY_N) {
+            denoiser.enabled = !denoiser.enabled;
+        }
+        if rl.is_key_pressed(KeyboardKey::KEY_R) {
+            temporal.enabled = !temporal.enabled;
+            temporal.reset();
+        }
+        if rl.is_key_pressed(KeyboardKey::KEY_U) {
+            dynres.enabled = !dynres.enabled;
+        }
+        if rl.is_key_pressed(KeyboardKey::KEY_X) {
+            upscale = upscale.next();
+        }
+        if rl.is_key_pressed(KeyboardKey::KEY_F6) {
+            denoiser.iterations = denoiser.iterations.saturating_sub(1).max(1);
+        }
+        if rl.is_key_pressed(KeyboardKey::KEY_F7) {
+            denoiser.iterations = (denoiser.iterations + 1).min(6);
+        }
+        if rl.is_key_pressed(KeyboardKey::KEY_F8) {
+            denoiser.sigma_color = (denoiser.sigma_color / 1.5).max(0.01);
+        }
+        if rl.is_key_pressed(KeyboardKey::KEY_F9) {
+            denoiser.sigma_color *= 1.5;
+        }
+        if animate {
+            anim_time += rl.get_frame_time();
+            let (objects, lights) = scene.edit();
+            timeline.apply(objects, lights, anim_time);
+            scene.settings.scene_time = anim_time;
+        }
+        if let Some(env) = scene.environment.as_mut() {
+            if rl.is_key_down(KeyboardKey::KEY_LEFT_BRACKET) {
+                env.rotate_deg(-2.0);
+            }
+            if rl.is_key_down(KeyboardKey::KEY_RIGHT_BRACKET) {
+                env.rotate_deg(2.0);
+            }
+            if rl.is_key_pressed(KeyboardKey::KEY_MINUS) {
+                env.intensity = (env.intensity - 0.1).max(0.0);
+            }
+            if rl.is_key_pressed(KeyboardKey::KEY_EQUAL) {
+                env.intensity += 0.1;
+            }
+            // Hora del día: el fondo cambia al instante, la iluminación se rehornea al soltar.
+            if let Some(sky) = env.physical_sky_mut() {
+                if rl.is_key_down(KeyboardKey::KEY_COMMA) {
+                    sky.set_time_of_day(sky.hours - 0.05);
+                }
+                if rl.is_key_down(KeyboardKey::KEY_PERIOD) {
+                    sky.set_time_of_day(sky.hours + 0.05);
+                }
+            }
+            if rl.is_key_released(KeyboardKey::KEY_COMMA)
+                || rl.is_key_released(KeyboardKey::KEY_PERIOD)
+            {
+                env.rebake();
+            }
+        }
+        scene.sun = scene
+            .environment
+            .as_ref()
+            .and_then(DirectionalLight::from_sky)
+            .unwrap_or_default();
 
-    // Charco junto al árbol (2x2)
-    for ax in 0..2 {
-        for az in 0..2 {
-            let p = vec3(
-                tree_base_x + (1 + ax) as f32 * b,
-                plat_y,
-                tree_base_z + az as f32 * b,
-            );
-            let water = Cube::from_center_size_rot(p, b, 0.0, 0.0, 0.0, mat_agua.clone());
-            objs.push(Object::Cube(water));
+        // Cualquier tecla, clic o ajuste continuo invalida el cuadro quieto.
+        let pose = (cam.pos, cam.yaw, cam.pitch);
+        let adjusting = [
+            KeyboardKey::KEY_LEFT_BRACKET,
+            KeyboardKey::KEY_RIGHT_BRACKET,
+            KeyboardKey::KEY_COMMA,
+            KeyboardKey::KEY_PERIOD,
+        ]
+        .iter()
+        .any(|k| rl.is_key_down(*k));
+        let interacted = rl.get_key_pressed().is_some()
+            || rl.is_mouse_button_pressed(MouseButton::MOUSE_BUTTON_LEFT)
+            || rl.is_mouse_button_pressed(MouseButton::MOUSE_BUTTON_RIGHT)
+            || rl.get_mouse_wheel_move() != 0.0
+            || adjusting;
+        // La animación solo impide el cuadro quieto si alguna pista mueve algo.
+        let animating = animate && !timeline.tracks.is_empty();
+        if pose != last_pose || interacted || animating || playing {
+            still_time = 0.0;
+            full_res_held = false;
+        } else {
+            still_time += rl.get_frame_time();
         }
-    }
+        last_pose = pose;
 
-    // ===================== LOOP INTERACTIVO =====================
-    while !rl.window_should_close() {
-        cam.update_from_input(&rl);
+        if !full_res_held {
+            // El tiempo del cuadro anterior solo vale si fue un cuadro dinámico normal.
+            if last_frame_dynamic {
+                dynres.update(rl.get_frame_time());
+            }
+            let full_res = still_time >= still_delay;
+            let (rw, rh) = if full_res {
+                (width, height)
+            } else if dynres.enabled {
+                dynres.size(width, height)
+            } else {
+                (width / 2, height / 2)
+            };
+            if (rw, rh) != (fb.width, fb.height) {
+                fb = FrameBuffer::new(rw, rh, Rgb::new(0, 0, 0));
+                features = aov::AovBuffer::new(rw, rh);
+                cam.set_aspect(rw as f32 / rh as f32);
+            }
 
-        let save_snap = rl.is_key_pressed(KeyboardKey::KEY_P);
+            if temporal.enabled {
+                cam.jitter = temporal.next_jitter();
+            }
+            // Si la animación o el editor movieron objetos, se rearma la BVH.
+            scene.commit();
+            renderer::render_to_fb(&cam, &mut fb, &scene);
+            if temporal.enabled || denoiser.enabled {
+                renderer::render_features(&cam, &mut features, &scene);
+            }
+            if temporal.enabled {
+                temporal.apply(&cam, &mut fb, &features);
+            }
+            if denoiser.enabled {
+                denoiser.apply(&mut fb, &features);
+            }
+            cam.jitter = (0.0, 0.0);
 
-        renderer::render_to_fb(&cam, &mut fb, &objs, skybox.as_ref(), &lights);
+            // Bilineal y cercano los hace la GPU al dibujar; el nítido se escala acá.
+            let stretched = (fb.width, fb.height) != (width, height);
+            let uploaded = if stretched && upscale == Upscale::Sharp {
+                fb.upscale_into(&mut screen);
+                presenter.upload(&mut rl, &thread, &screen, false)
+            } else {
+                presenter.upload(&mut rl, &thread, &fb, upscale == Upscale::Bilinear)
+            };
+            if let Err(e) = uploaded {
+                eprintln!("{e}");
+            }
+            full_res_held = full_res;
+            last_frame_dynamic = dynres.enabled && !full_res;
+        } else {
+            last_frame_dynamic = false;
+        }
 
         {
             let mut d = rl.begin_drawing(&thread);
             d.clear_background(Color::BLACK);
-            fb.present_scaled(&mut d, 0, 0, scale);
+            presenter.draw(&mut d, 0, 0, width, height);
+            d.draw_text(
+                "WASD/QE mover — Flechas/mouse rotar (Tab captura) — Shift rápido — P = snapshot",
+                10,
+                10,
+                18,
+                Color::WHITE,
+            );
+            d.draw_text(
+                &format!(
+                    "O = AO — M/1-0 = vista ({}) — I = IBL — [ ] girar cielo — -/= intensidad — ,/. hora",
+                    scene.settings.mode.name()
+                ),
+                10,
+                32,
+                18,
+                Color::WHITE,
+            );
+            let path_status = if playing {
+                format!("reproduciendo {play_time:.1} / {:.1} s", cam_path.duration())
+            } else {
+                format!("{} keyframes", cam_path.keys.len())
+            };
             d.draw_text(
-                "WASD/QE mover — Flechas rotar — Shift rápido — P = snapshot",
+                &format!("K = keyframe — J = quitar — H = reproducir — F2 = guardar ({path_status})"),
                 10,
+                54,
+                18,
+                Color::WHITE,
+            );
+            d.draw_text(
+                &format!(
+                    "V = proyección ({}) — B = estéreo ({}) — G = {} — Espacio saltar — T = animación",
+                    cam.projection.name(),
+                    cam.stereo.name(),
+                    if walker.enabled { "caminar" } else { "volar" }
+                ),
                 10,
+                76,
                 18,
                 Color::WHITE,
             );
+            d.draw_text(
+                &format!(
+                    "N = denoiser ({}) — F6/F7 pasadas ({}) — F8/F9 tolerancia color ({:.2}) — R = temporal ({}) — F5 = AOV",
+                    if denoiser.enabled { "sí" } else { "no" },
+                    denoiser.iterations,
+                    denoiser.sigma_color,
+                    if temporal.enabled { "sí" } else { "no" }
+                ),
+                10,
+                98,
+                18,
+                Color::WHITE,
+            );
+            d.draw_text(
+                &format!(
+                    "U = resolución dinámica ({}, {}x{}, objetivo {:.0} fps) — X = escalado ({})",
+                    if dynres.enabled { "sí" } else { "no" },
+                    fb.width,
+                    fb.height,
+                    dynres.target_fps,
+                    upscale.name()
+                ),
+                10,
+                120,
+                18,
+                Color::WHITE,
+            );
+            if editor.enabled {
+                d.draw_text(
+                    &format!(
+                        "EDITOR (F3) — material: {} (rueda) — clic izq. quitar / der. poner — Ctrl+Z{} — Ctrl+S guardar",
+                        editor.material().name,
+                        if editor.can_undo() { "" } else { " (nada)" }
+                    ),
+                    10,
+                    142,
+                    18,
+                    Color::YELLOW,
+                );
+                let (cx, cy) = (width / 2, height / 2);
+                d.draw_line(cx - 8, cy, cx + 8, cy, Color::WHITE);
+                d.draw_line(cx, cy - 8, cx, cy + 8, Color::WHITE);
+            }
+            if inspector.enabled {
+                d.draw_text(
+                    "INSPECTOR (F4) — clic = inspeccionar objeto",
+                    10,
+                    142,
+                    18,
+                    Color::SKYBLUE,
+                );
+                inspector.draw(&mut d, scene.objects());
+            }
             d.draw_fps(10, height - 24);
         } 
         if save_snap {
-            let _ = fb.render_to_file("output_live.png");
+            match fb.render_to_file("output_live.png") {
+                Ok(()) => println!("Captura guardada en output_live.png"),
+                Err(e) => eprintln!("No pude guardar la captura ({e})"),
+            }
+        }
+        // F5: las AOV de la vista actual, a la resolución del FB.
+        if save_aov {
+            let mut aovs = aov::AovBuffer::new(fb.width, fb.height);
+            renderer::render_aovs(&cam, &mut aovs, &scene);
+            match aovs.write_exr("aov_live.exr") {
+                Ok(()) => println!("AOV guardadas en aov_live.exr"),
+                Err(e) => eprintln!("No pude guardar las AOV ({e})"),
+            }
         }
     }
 }
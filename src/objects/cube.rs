@@ -1,8 +1,16 @@
 use crate::color::Material;
 use crate::intersect::{Intersect, RayIntersect};
-use glm::{Mat3, Vec3, vec3};
+use glm::{Mat3, Quat, Vec3, vec3};
 use nalgebra_glm as glm;
 
+// Pose al cerrar el obturador; la de apertura es `center`/`rot` del cubo.
+#[derive(Clone)]
+pub struct CubeMotion {
+    pub center_end: Vec3,
+    q_start: Quat,
+    q_end: Quat,
+}
+
 #[derive(Clone)]
 pub struct Cube {
     pub center: Vec3,
@@ -10,6 +18,34 @@ pub struct Cube {
     pub rot: Mat3,
     pub rot_inv: Mat3,
     pub material: Material,
+    pub motion: Option<CubeMotion>,
+}
+
+// Yaw (Y), pitch (X) y roll (Z) en grados, aplicados en ese orden.
+pub fn euler_rotation(yaw_deg: f32, pitch_deg: f32, roll_deg: f32) -> Mat3 {
+    glm::quat_to_mat3(&euler_quat(yaw_deg, pitch_deg, roll_deg))
+}
+
+// Inversa de `euler_rotation`: (yaw, pitch, roll) en grados. Con pitch de ±90 el roll
+// queda en 0 y el yaw absorbe el giro.
+pub fn euler_angles(rot: &Mat3) -> (f32, f32, f32) {
+    let pitch = (-rot[(1, 2)]).clamp(-1.0, 1.0).asin();
+    let (yaw, roll) = if pitch.cos() > 1e-4 {
+        (
+            rot[(0, 2)].atan2(rot[(2, 2)]),
+            rot[(1, 0)].atan2(rot[(1, 1)]),
+        )
+    } else {
+        ((-rot[(2, 0)]).atan2(rot[(0, 0)]), 0.0)
+    };
+    (yaw.to_degrees(), pitch.to_degrees(), roll.to_degrees())
+}
+
+fn euler_quat(yaw_deg: f32, pitch_deg: f32, roll_deg: f32) -> Quat {
+    let qy = glm::quat_angle_axis(yaw_deg.to_radians(), &vec3(0.0, 1.0, 0.0));
+    let qp = glm::quat_angle_axis(pitch_deg.to_radians(), &vec3(1.0, 0.0, 0.0));
+    let qr = glm::quat_angle_axis(roll_deg.to_radians(), &vec3(0.0, 0.0, 1.0));
+    qy * qp * qr
 }
 
 impl Cube {
@@ -22,11 +58,7 @@ impl Cube {
         material: Material,
     ) -> Self {
         let half = vec3(size * 0.5, size * 0.5, size * 0.5);
-        let qy = glm::quat_angle_axis(yaw_deg.to_radians(), &vec3(0.0, 1.0, 0.0));
-        let qp = glm::quat_angle_axis(pitch_deg.to_radians(), &vec3(1.0, 0.0, 0.0));
-        let qr = glm::quat_angle_axis(roll_deg.to_radians(), &vec3(0.0, 0.0, 1.0));
-        let q = qy * qp * qr;
-        let rot = glm::quat_to_mat3(&q);
+        let rot = euler_rotation(yaw_deg, pitch_deg, roll_deg);
         let rot_inv = rot.transpose();
         Self {
             center,
@@ -34,21 +66,59 @@ impl Cube {
             rot,
             rot_inv,
             material,
+            motion: None,
         }
     }
     pub fn set_rotation_euler(&mut self, yaw_deg: f32, pitch_deg: f32, roll_deg: f32) {
-        let qy = glm::quat_angle_axis(yaw_deg.to_radians(), &vec3(0.0, 1.0, 0.0));
-        let qp = glm::quat_angle_axis(pitch_deg.to_radians(), &vec3(1.0, 0.0, 0.0));
-        let qr = glm::quat_angle_axis(roll_deg.to_radians(), &vec3(0.0, 0.0, 1.0));
-        self.rot = glm::quat_to_mat3(&(qy * qp * qr));
-        self.rot_inv = self.rot.transpose();
+        self.set_rotation(euler_rotation(yaw_deg, pitch_deg, roll_deg));
+    }
+    pub fn set_rotation(&mut self, rot: Mat3) {
+        self.rot = rot;
+        self.rot_inv = rot.transpose();
     }
 }
 
-impl RayIntersect for Cube {
-    fn ray_intersect(&self, ro_w: &Vec3, rd_w: &Vec3) -> Intersect {
-        let ro = self.rot_inv * (ro_w - self.center);
-        let rd = self.rot_inv * *rd_w;
+impl Cube {
+    // Movimiento durante el obturador: de la pose actual a `center_end`, girando hasta
+    // `rot_end` o sin girar si es `None`.
+    pub fn set_motion(&mut self, center_end: Vec3, rot_end: Option<Mat3>) {
+        let q_start = glm::mat3_to_quat(&self.rot);
+        let q_end = match rot_end {
+            Some(r) => glm::mat3_to_quat(&r),
+            None => q_start,
+        };
+        self.motion = Some(CubeMotion {
+            center_end,
+            q_start,
+            q_end,
+        });
+    }
+
+    pub fn bounds(&self) -> (Vec3, Vec3) {
+        let Some(m) = &self.motion else {
+            // Media extensión de la caja girada.
+            let ext = self.rot.abs() * self.half;
+            return (self.center - ext, self.center + ext);
+        };
+        // Mientras gira puede asomar cualquier esquina: esfera circunscrita en los dos extremos.
+        let r = glm::length(&self.half);
+        let ext = vec3(r, r, r);
+        (
+            (self.center - ext).inf(&(m.center_end - ext)),
+            (self.center + ext).sup(&(m.center_end + ext)),
+        )
+    }
+
+    fn intersect_posed(
+        &self,
+        center: &Vec3,
+        rot: &Mat3,
+        rot_inv: &Mat3,
+        ro_w: &Vec3,
+        rd_w: &Vec3,
+    ) -> Intersect {
+        let ro = rot_inv * (ro_w - center);
+        let rd = rot_inv * rd_w;
 
         let min = -self.half;
         let max = self.half;
@@ -94,8 +164,9 @@ impl RayIntersect for Cube {
 
         let p_local = ro + rd * t_hit;
         let eps = 1e-3;
-        let size = (max - min);
-        let (mut n_local, mut uv) = (vec3(0.0, 0.0, 0.0), (0.0_f32, 0.0_f32));
+        let size = max - min;
+        let n_local;
+        let mut uv = (0.0_f32, 0.0_f32);
         if (p_local.x - min.x).abs() < eps {
             n_local = vec3(-1.0, 0.0, 0.0);
             uv.0 = (p_local.z - min.z) / size.z;
@@ -121,9 +192,24 @@ impl RayIntersect for Cube {
             uv.0 = (p_local.x - min.x) / size.x;
             uv.1 = (p_local.y - min.y) / size.y;
         }
-        let p_world = self.center + self.rot * p_local;
-        let n_world = self.rot * n_local;
+        let p_world = center + rot * p_local;
+        let n_world = rot * n_local;
 
         Intersect::new(p_world, n_world, t_hit, self.material.clone(), Some(uv))
     }
 }
+
+impl RayIntersect for Cube {
+    fn ray_intersect(&self, ro_w: &Vec3, rd_w: &Vec3) -> Intersect {
+        self.intersect_posed(&self.center, &self.rot, &self.rot_inv, ro_w, rd_w)
+    }
+
+    fn ray_intersect_at(&self, ro_w: &Vec3, rd_w: &Vec3, time: f32) -> Intersect {
+        let Some(m) = self.motion.as_ref().filter(|_| time > 0.0) else {
+            return self.ray_intersect(ro_w, rd_w);
+        };
+        let center = glm::lerp(&self.center, &m.center_end, time);
+        let rot = glm::quat_to_mat3(&glm::quat_slerp(&m.q_start, &m.q_end, time));
+        self.intersect_posed(&center, &rot, &rot.transpose(), ro_w, rd_w)
+    }
+}
@@ -1,6 +1,7 @@
 use glm::Vec3;
 use nalgebra_glm as glm;
 
+use crate::color::Material;
 use crate::intersect::{Intersect, RayIntersect};
 
 pub mod cube;
@@ -9,11 +10,36 @@ pub use cube::Cube;
 pub mod plane; 
 pub use plane::Plane; 
 
+#[derive(Clone)]
 pub enum Object {
     Cube(Cube),
     Plane(Plane),
 }
 
+impl Object {
+    pub fn material(&self) -> &Material {
+        match self {
+            Object::Cube(c) => &c.material,
+            Object::Plane(p) => &p.material,
+        }
+    }
+
+    pub fn material_mut(&mut self) -> &mut Material {
+        match self {
+            Object::Cube(c) => &mut c.material,
+            Object::Plane(p) => &mut p.material,
+        }
+    }
+
+    // Caja (mín, máx) que lo contiene durante todo el obturador; los planos no tienen.
+    pub fn bounds(&self) -> Option<(Vec3, Vec3)> {
+        match self {
+            Object::Cube(c) => Some(c.bounds()),
+            Object::Plane(_) => None,
+        }
+    }
+}
+
 impl RayIntersect for Object {
     fn ray_intersect(&self, ro: &Vec3, rd: &Vec3) -> Intersect {
         match self {
@@ -21,4 +47,11 @@ impl RayIntersect for Object {
             Object::Plane(p) => p.ray_intersect(ro, rd), 
         }
     }
+
+    fn ray_intersect_at(&self, ro: &Vec3, rd: &Vec3, time: f32) -> Intersect {
+        match self {
+            Object::Cube(c) => c.ray_intersect_at(ro, rd, time),
+            Object::Plane(p) => p.ray_intersect_at(ro, rd, time),
+        }
+    }
 }
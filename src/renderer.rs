@@ -1,12 +1,23 @@
-use crate::camera::Camera;
-use crate::color::{Material, Rgb};
+use crate::aov::{AovBuffer, AovSample};
+use crate::camera::{Camera, StereoLayout};
+use crate::color::Rgb;
+use crate::environment::Environment;
 use crate::intersect::{Intersect, RayIntersect};
 use crate::objects::Object;
+use crate::sampling::{self, Rng};
+use crate::scene::Scene;
 use nalgebra_glm as glm;
-use raylib::prelude::*;
+use std::cell::Cell;
+use std::ops::ControlFlow;
 
 const EPS: f32 = 1e-3;
 const MAX_DEPTH: i32 = 3;
+// Rugosidad equivalente al lóbulo Phong fijo (exponente 50) de `shade`.
+const IBL_ROUGHNESS: f32 = 0.2;
+// Distancia que la vista de profundidad lleva a negro.
+const DEBUG_DEPTH_FAR: f32 = 20.0;
+// Pruebas `ray_intersect` por píxel que la vista de costo pinta al máximo (escala log).
+const DEBUG_COST_MAX: f32 = 200_000.0;
 
 #[derive(Clone, Copy, Debug)]
 pub struct PointLight {
@@ -15,9 +26,133 @@ pub struct PointLight {
     pub intensity: f32,
 }
 
-fn sky_color(dir: &glm::Vec3, skybox: Option<&crate::texture::Texture>) -> Rgb {
-    if let Some(tex) = skybox {
-        return tex.sample_dir_equirect(dir);
+// `dir` es la dirección en la que viaja la luz (del sol hacia la escena).
+#[derive(Clone, Copy, Debug)]
+pub struct DirectionalLight {
+    pub dir: glm::Vec3,
+    pub color: Rgb,
+    pub intensity: f32,
+}
+
+impl Default for DirectionalLight {
+    fn default() -> Self {
+        Self {
+            dir: glm::normalize(&glm::vec3(-0.6, -1.0, -0.4)),
+            color: Rgb::new(255, 255, 255),
+            intensity: 1.0,
+        }
+    }
+}
+
+impl DirectionalLight {
+    pub fn from_sky(env: &Environment) -> Option<Self> {
+        let sky = env.physical_sky()?;
+        let to_sun = env.sun_dir()?;
+        let (color, intensity) = sky.sun_light();
+        Some(Self {
+            dir: -to_sun,
+            color: Rgb::from_vec3(&color),
+            intensity,
+        })
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct AoSettings {
+    pub enabled: bool,
+    pub radius: f32,
+    pub samples: u32,
+}
+
+impl Default for AoSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            radius: 0.8,
+            samples: 8,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RenderMode {
+    Shaded,
+    AmbientOcclusion,
+    // Vistas de depuración: reemplazan el color sombreado del rayo primario.
+    Normals,
+    Uv,
+    Depth,
+    MaterialId,
+    ObjectId,
+    // Profundidad máxima de rebote alcanzada (reflexión/refracción).
+    Bounces,
+    // Fracción del sol y las luces puntuales que ve el punto.
+    Shadow,
+    // Mapa de calor de las llamadas a `ray_intersect` del píxel.
+    Cost,
+}
+
+impl RenderMode {
+    // En el orden de las teclas 1..9, 0.
+    pub const ALL: [(RenderMode, &'static str); 10] = [
+        (RenderMode::Shaded, "sombreado"),
+        (RenderMode::AmbientOcclusion, "ao"),
+        (RenderMode::Normals, "normales"),
+        (RenderMode::Uv, "uv"),
+        (RenderMode::Depth, "profundidad"),
+        (RenderMode::MaterialId, "material"),
+        (RenderMode::ObjectId, "objeto"),
+        (RenderMode::Bounces, "rebotes"),
+        (RenderMode::Shadow, "sombras"),
+        (RenderMode::Cost, "costo"),
+    ];
+
+    pub fn from_name(name: &str) -> Option<RenderMode> {
+        Self::ALL.iter().find(|(_, n)| *n == name).map(|(m, _)| *m)
+    }
+
+    pub fn name(self) -> &'static str {
+        Self::ALL
+            .iter()
+            .find(|(m, _)| *m == self)
+            .map_or("", |(_, n)| n)
+    }
+
+    fn is_debug(self) -> bool {
+        !matches!(self, RenderMode::Shaded | RenderMode::AmbientOcclusion)
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct RenderSettings {
+    pub mode: RenderMode,
+    pub ao: AoSettings,
+    pub ibl: bool,
+    pub env_samples: u32,
+    pub seed: u32,
+    // Muestras por píxel repartidas en el obturador; 1 = sin motion blur.
+    pub motion_samples: u32,
+    // Segundos de escena (ondas del agua, animaciones de materiales).
+    pub scene_time: f32,
+}
+
+impl Default for RenderSettings {
+    fn default() -> Self {
+        Self {
+            mode: RenderMode::Shaded,
+            ao: AoSettings::default(),
+            ibl: false,
+            env_samples: 0,
+            seed: 0,
+            motion_samples: 1,
+            scene_time: 0.0,
+        }
+    }
+}
+
+fn sky_color(dir: &glm::Vec3, skybox: Option<&Environment>) -> Rgb {
+    if let Some(env) = skybox {
+        return Rgb::from_vec3(&env.radiance(dir));
     }
     let top = Rgb::new(110, 160, 220);
     let bottom = Rgb::new(20, 40, 90);
@@ -25,15 +160,132 @@ fn sky_color(dir: &glm::Vec3, skybox: Option<&crate::texture::Texture>) -> Rgb {
     Rgb::lerp(bottom, top, t.clamp(0.0, 1.0))
 }
 
-fn scene_intersect(ro: &glm::Vec3, rd: &glm::Vec3, objects: &[Object]) -> Intersect {
+// Contadores del píxel en curso para las vistas de costo y rebotes (el render es de un hilo).
+thread_local! {
+    static RAY_TESTS: Cell<u32> = const { Cell::new(0) };
+    static DEEPEST: Cell<i32> = const { Cell::new(0) };
+}
+
+// Todas las pruebas contra objetos pasan por aquí para poder contarlas.
+fn hit_at(o: &Object, ro: &glm::Vec3, rd: &glm::Vec3, time: f32) -> Intersect {
+    RAY_TESTS.with(|c| c.set(c.get() + 1));
+    o.ray_intersect_at(ro, rd, time)
+}
+
+// Más cercano y su índice en `scene.objects()` (como `Scene::intersect`, pero contado).
+fn scene_intersect_index(
+    ro: &glm::Vec3,
+    rd: &glm::Vec3,
+    scene: &Scene,
+    time: f32,
+) -> (Option<usize>, Intersect) {
     let mut closest = Intersect::empty();
-    for o in objects {
-        let hit = o.ray_intersect(ro, rd);
-        if hit.is_intersecting && hit.distance < closest.distance {
+    let mut index = None;
+    scene.traverse(ro, rd, f32::INFINITY, |i, o| {
+        let hit = hit_at(o, ro, rd, time);
+        if hit.is_intersecting
+            && (hit.distance < closest.distance
+                || (hit.distance == closest.distance && index.is_some_and(|j| i < j)))
+        {
             closest = hit;
+            index = Some(i);
+        }
+        ControlFlow::Continue(closest.distance)
+    });
+    (index, closest)
+}
+
+fn scene_intersect(ro: &glm::Vec3, rd: &glm::Vec3, scene: &Scene, time: f32) -> Intersect {
+    scene_intersect_index(ro, rd, scene, time).1
+}
+
+// Algún objeto corta el rayo antes de `max_dist` (rayos de sombra).
+fn occluded(scene: &Scene, origin: &glm::Vec3, dir: &glm::Vec3, max_dist: f32, time: f32) -> bool {
+    let mut blocked = false;
+    scene.traverse(origin, dir, max_dist, |_, o| {
+        let h = hit_at(o, origin, dir, time);
+        if h.is_intersecting && h.distance < max_dist {
+            blocked = true;
+            return ControlFlow::Break(());
         }
+        ControlFlow::Continue(f32::INFINITY)
+    });
+    blocked
+}
+
+// 1.0 = totalmente abierto, 0.0 = totalmente ocluido dentro de `radius`.
+fn ambient_occlusion(
+    hit: &Intersect,
+    scene: &Scene,
+    ao: &AoSettings,
+    seed: u32,
+    time: f32,
+) -> f32 {
+    if ao.samples == 0 || ao.radius <= 0.0 {
+        return 1.0;
+    }
+    let origin = hit.point + hit.normal * EPS;
+    let mut rng = Rng::from_point(&hit.point, seed);
+    let mut occlusion = 0.0;
+    for _ in 0..ao.samples {
+        let local = sampling::cosine_hemisphere(rng.next_f32(), rng.next_f32());
+        let dir = sampling::to_world(&local, &hit.normal);
+        let mut nearest = ao.radius;
+        scene.traverse(&origin, &dir, ao.radius, |_, o| {
+            let h = hit_at(o, &origin, &dir, time);
+            if h.is_intersecting && h.distance < nearest {
+                nearest = h.distance;
+            }
+            ControlFlow::Continue(nearest)
+        });
+        occlusion += 1.0 - nearest / ao.radius;
+    }
+    1.0 - occlusion / ao.samples as f32
+}
+
+// Irradiancia del entorno (ya dividida por pi). Con `env_samples` > 0 se estima con
+// rayos de sombra muestreados por importancia; si no, se usa el SH sin sombras * AO.
+fn environment_diffuse(
+    hit: &Intersect,
+    scene: &Scene,
+    env: &Environment,
+    settings: &RenderSettings,
+    ao: f32,
+    time: f32,
+) -> glm::Vec3 {
+    if settings.env_samples == 0 {
+        return env.irradiance(&hit.normal) * ao;
+    }
+    let origin = hit.point + hit.normal * EPS;
+    let mut rng = Rng::from_point(&hit.point, settings.seed ^ 0x5bd1e995);
+    let mut acc = glm::vec3(0.0, 0.0, 0.0);
+    for _ in 0..settings.env_samples {
+        let s = env.sample(rng.next_f32(), rng.next_f32());
+        let cos = glm::dot(&hit.normal, &s.dir);
+        if cos <= 0.0 || s.pdf <= 0.0 {
+            continue;
+        }
+        if occluded(scene, &origin, &s.dir, f32::INFINITY, time) {
+            continue;
+        }
+        acc += s.radiance * (cos / (std::f32::consts::PI * s.pdf));
+    }
+    acc / settings.env_samples as f32
+}
+
+// Perturba la normal de caras hacia arriba con dos trenes de ondas que avanzan con el tiempo.
+fn ripple_normal(hit: &Intersect, scene_time: f32) -> glm::Vec3 {
+    let amp = hit.material.ripple;
+    if amp <= 0.0 || hit.normal.y < 0.5 {
+        return hit.normal;
     }
-    closest
+    let p = hit.point;
+    let (k1, k2) = (14.0, 9.0);
+    let a1 = k1 * (p.x * 0.8 + p.z * 0.6) + scene_time * 2.1;
+    let a2 = k2 * (p.z * 0.9 - p.x * 0.4) + scene_time * 1.3;
+    let dx = 0.8 * a1.cos() - 0.4 * a2.cos() * 0.6;
+    let dz = 0.6 * a1.cos() + 0.9 * a2.cos() * 0.6;
+    glm::normalize(&(hit.normal - glm::vec3(dx, 0.0, dz) * amp))
 }
 
 fn reflect(i: &glm::Vec3, n: &glm::Vec3) -> glm::Vec3 {
@@ -54,85 +306,109 @@ fn fresnel_schlick(cos_theta: f32, f0: f32) -> f32 {
     f0 + (1.0 - f0) * (1.0 - cos_theta).powf(5.0)
 }
 
+#[allow(clippy::too_many_arguments)]
 fn shade(
     hit: &Intersect,
     ro: &glm::Vec3,
     rd: &glm::Vec3,
-    objects: &[Object],
+    scene: &Scene,
+    settings: &RenderSettings,
     depth: i32,
-    skybox: Option<&crate::texture::Texture>,
-    lights: &[PointLight],
+    time: f32,
+    aov: Option<&mut AovSample>,
 ) -> Rgb {
-    let sun_dir = glm::normalize(&glm::vec3(-0.6, -1.0, -0.4));
-    let ambient = 0.12;
-    let mut lambert = glm::dot(&hit.normal, &(-sun_dir)).max(0.0);
+    // Componentes para las AOV; se calculan siempre (son baratas) y se copian si hay destino.
+    let mut parts = AovSample::default();
+    let sun = &scene.sun;
+    let sun_dir = sun.dir;
+    let ao = if settings.ao.enabled {
+        ambient_occlusion(hit, scene, &settings.ao, settings.seed, time)
+    } else {
+        1.0
+    };
+    let ambient = scene.ambient * ao;
+    let mut lambert = glm::dot(&hit.normal, &(-sun_dir)).max(0.0) * sun.intensity;
 
-    let shadow = {
+    let shadow = if sun.intensity <= 0.0 {
+        0.0
+    } else {
         let origin = hit.point + hit.normal * EPS;
-        let mut blocked = false;
-        for o in objects {
-            let h = o.ray_intersect(&origin, &(-sun_dir));
-            if h.is_intersecting {
-                blocked = true;
-                break;
-            }
-        }
+        let blocked = occluded(scene, &origin, &(-sun_dir), f32::INFINITY, time);
         if blocked { 0.0 } else { 1.0 }
     };
     lambert *= shadow;
 
-    let base = hit.material.sample_albedo(hit.uv);
-    let mut col = base.scale(ambient + lambert);
+    let base = hit.material.sample_albedo(hit.uv, settings.scene_time);
+    let sun_base = Rgb::mul(base, sun.color);
+    let sun_diffuse = sun_base.scale(lambert);
+    parts.albedo = base.to_vec3();
+    parts.normal = hit.normal;
+    parts.diffuse = sun_diffuse.to_vec3();
+    let env = scene.environment.as_ref().filter(|_| settings.ibl);
+    let mut col = match env {
+        Some(env) => {
+            let irr = environment_diffuse(hit, scene, env, settings, ao, time);
+            let amb = Rgb::from_vec3(&base.to_vec3().component_mul(&irr));
+            Rgb::add(amb, sun_diffuse)
+        }
+        None => Rgb::add(base.scale(ambient), sun_diffuse),
+    };
 
     if hit.material.specular > 0.0 {
         let view = glm::normalize(&(ro - hit.point));
         let halfv = glm::normalize(&(-sun_dir + view));
         let spec =
             glm::dot(&hit.normal, &halfv).max(0.0).powf(50.0) * hit.material.specular * shadow;
-        col = Rgb::add(col, Rgb::new(255, 255, 255).scale(spec));
+        let sun_spec = sun.color.scale(spec * sun.intensity);
+        parts.specular += sun_spec.to_vec3();
+        col = Rgb::add(col, sun_spec);
+        if let Some(env) = env {
+            let rdir = reflect(rd, &hit.normal);
+            let ibl_spec = env.specular(&rdir, IBL_ROUGHNESS) * (hit.material.specular * ao);
+            col = Rgb::add(col, Rgb::from_vec3(&ibl_spec));
+        }
     }
 
     if hit.material.emission_strength > 0.0 {
         let e = hit.material.emission.scale(hit.material.emission_strength);
+        parts.emission = e.to_vec3();
         col = Rgb::add(col, e);
     }
 
-    for light in lights {
-        let toL = light.pos - hit.point;
-        let r2 = glm::dot(&toL, &toL).max(1e-6);
+    for light in &scene.lights {
+        let to_light = light.pos - hit.point;
+        let r2 = glm::dot(&to_light, &to_light).max(1e-6);
         let r = r2.sqrt();
-        let L = toL / r;
+        let dir = to_light / r;
 
         let origin = hit.point + hit.normal * EPS;
-        let mut blocked = false;
-        for o in objects {
-            let h = o.ray_intersect(&origin, &L);
-            if h.is_intersecting && h.distance < r - EPS {
-                blocked = true;
-                break;
-            }
-        }
-        if blocked {
+        if occluded(scene, &origin, &dir, r - EPS, time) {
             continue;
         }
 
         let atten = light.intensity / r2;
 
-        let ndotl = glm::dot(&hit.normal, &L).max(0.0);
+        let ndotl = glm::dot(&hit.normal, &dir).max(0.0);
         if ndotl > 0.0 {
             let diff_col = light.color.scale(ndotl * atten);
+            parts.diffuse += diff_col.to_vec3();
             col = Rgb::add(col, diff_col);
         }
         if hit.material.specular > 0.0 {
             let view = glm::normalize(&(ro - hit.point));
-            let halfv = glm::normalize(&(L + view));
+            let halfv = glm::normalize(&(dir + view));
             let spec =
                 glm::dot(&hit.normal, &halfv).max(0.0).powf(50.0) * hit.material.specular * atten;
-            col = Rgb::add(col, Rgb::new(255, 255, 255).scale(spec));
+            let spec_col = Rgb::new(255, 255, 255).scale(spec);
+            parts.specular += spec_col.to_vec3();
+            col = Rgb::add(col, spec_col);
         }
     }
 
     if depth >= MAX_DEPTH {
+        if let Some(a) = aov {
+            *a = parts;
+        }
         return col;
     }
 
@@ -152,83 +428,318 @@ fn shade(
     if hit.material.reflectivity > 0.0 || (hit.material.transparency > 0.0 && fres > 0.0) {
         let rdir = glm::normalize(&reflect(rd, &hit.normal));
         let rorig = hit.point + hit.normal * EPS;
-        let rcol = cast_ray(&rorig, &rdir, objects, depth + 1, skybox, lights);
+        let rcol = cast_ray(&rorig, &rdir, scene, settings, depth + 1, time);
         let mixf = (hit.material.reflectivity + fres).clamp(0.0, 1.0);
+        parts.fade(mixf);
+        parts.reflection += rcol.to_vec3() * mixf;
         col = Rgb::lerp(col, rcol, mixf);
     }
 
-    if hit.material.transparency > 0.0 {
-        if let Some(tdir) = refract(rd, &n, etai_over_etat) {
-            let torig = hit.point - n * EPS; // empuja hacia adentro
-            let tcol = cast_ray(
-                &torig,
-                &glm::normalize(&tdir),
-                objects,
-                depth + 1,
-                skybox,
-                lights,
-            );
-            let atten = 0.85;
-            let tcol_att = tcol.scale(atten);
-            let mixf = (hit.material.transparency * (1.0 - fres)).clamp(0.0, 1.0);
-            col = Rgb::lerp(col, tcol_att, mixf);
-        }
+    if hit.material.transparency > 0.0
+        && let Some(tdir) = refract(rd, &n, etai_over_etat)
+    {
+        let torig = hit.point - n * EPS; // empuja hacia adentro
+        let tcol = cast_ray(
+            &torig,
+            &glm::normalize(&tdir),
+            scene,
+            settings,
+            depth + 1,
+            time,
+        );
+        let atten = 0.85;
+        let tcol_att = tcol.scale(atten);
+        let mixf = (hit.material.transparency * (1.0 - fres)).clamp(0.0, 1.0);
+        parts.fade(mixf);
+        parts.refraction += tcol_att.to_vec3() * mixf;
+        col = Rgb::lerp(col, tcol_att, mixf);
     }
 
+    if let Some(a) = aov {
+        *a = parts;
+    }
     col
 }
 
+// `settings` puede diferir de `scene.settings` (las vistas de depuración sombrean aparte).
 fn cast_ray(
     ro: &glm::Vec3,
     rd: &glm::Vec3,
-    objects: &[Object],
+    scene: &Scene,
+    settings: &RenderSettings,
     depth: i32,
-    skybox: Option<&crate::texture::Texture>,
-    lights: &[PointLight],
+    time: f32,
 ) -> Rgb {
-    let hit = scene_intersect(ro, rd, objects);
+    if depth == 0 && settings.mode.is_debug() {
+        return debug_color(ro, rd, scene, settings, time);
+    }
+    DEEPEST.with(|c| c.set(c.get().max(depth)));
+    let mut hit = scene_intersect(ro, rd, scene, time);
+    hit.normal = ripple_normal(&hit, settings.scene_time);
+    if settings.mode == RenderMode::AmbientOcclusion {
+        if !hit.is_intersecting {
+            return Rgb::new(255, 255, 255);
+        }
+        let ao = ambient_occlusion(&hit, scene, &settings.ao, settings.seed, time);
+        return Rgb::new(255, 255, 255).scale(ao);
+    }
+    if !hit.is_intersecting {
+        return sky_color(rd, scene.environment.as_ref());
+    }
+    let col = shade(&hit, ro, rd, scene, settings, depth, time, None);
+    scene.fog.apply(col, hit.distance)
+}
+
+// Rayo primario con todas las pasadas; la vista (`settings.mode`) no afecta a las AOV.
+fn trace_aov(ro: &glm::Vec3, rd: &glm::Vec3, scene: &Scene, time: f32) -> AovSample {
+    let mut hit = scene_intersect(ro, rd, scene, time);
+    hit.normal = ripple_normal(&hit, scene.settings.scene_time);
     if !hit.is_intersecting {
-        return sky_color(rd, skybox);
+        return AovSample {
+            beauty: sky_color(rd, scene.environment.as_ref()).to_vec3(),
+            ..AovSample::default()
+        };
     }
-    shade(&hit, ro, rd, objects, depth, skybox, lights)
+    let settings = RenderSettings {
+        mode: RenderMode::Shaded,
+        ..scene.settings
+    };
+    let mut a = AovSample::default();
+    let beauty = shade(&hit, ro, rd, scene, &settings, 0, time, Some(&mut a));
+    // La niebla solo va en beauty; las pasadas quedan sin ella.
+    a.beauty = scene.fog.apply(beauty, hit.distance).to_vec3();
+    a.depth = hit.distance;
+    a.shadow = light_visibility(&hit, scene, time);
+    a
+}
+
+// Color estable a partir de un entero (ids de objeto y material).
+fn id_color(id: u32) -> Rgb {
+    let mut x = id.wrapping_add(1).wrapping_mul(0x9E37_79B9);
+    x ^= x >> 15;
+    x = x.wrapping_mul(0x85EB_CA6B);
+    x ^= x >> 13;
+    Rgb::new(
+        64 + (x & 0xBF) as u8,
+        64 + ((x >> 8) & 0xBF) as u8,
+        64 + ((x >> 16) & 0xBF) as u8,
+    )
+}
+
+// Negro → azul → rojo → amarillo → blanco, con `t` en [0, 1].
+fn heat_color(t: f32) -> Rgb {
+    let stops = [
+        glm::vec3(0.0, 0.0, 0.0),
+        glm::vec3(0.0, 0.0, 1.0),
+        glm::vec3(1.0, 0.0, 0.0),
+        glm::vec3(1.0, 1.0, 0.0),
+        glm::vec3(1.0, 1.0, 1.0),
+    ];
+    let x = t.clamp(0.0, 1.0) * (stops.len() - 1) as f32;
+    let i = (x.floor() as usize).min(stops.len() - 2);
+    Rgb::from_vec3(&glm::lerp(&stops[i], &stops[i + 1], x - i as f32))
 }
 
-pub fn render(
-    cam: &Camera,
-    width: i32,
-    height: i32,
-    objects: &[Object],
-    d: &mut raylib::prelude::RaylibDrawHandle,
-    skybox: Option<&crate::texture::Texture>,
-    lights: &[PointLight],
-) {
-    for y in 0..height {
-        for x in 0..width {
-            let dir = cam.ray_dir(x, y, width, height);
-            let c = cast_ray(&cam.pos, &dir, objects, 0, skybox, lights).to_raylib();
-            d.draw_pixel(x, y, c);
+// Fracción de luces (sol incluido si alumbra) que ven el punto sin obstáculos.
+fn light_visibility(hit: &Intersect, scene: &Scene, time: f32) -> f32 {
+    let origin = hit.point + hit.normal * EPS;
+    let mut visible = 0;
+    let mut total = 0;
+    if scene.sun.intensity > 0.0 {
+        total += 1;
+        if !occluded(scene, &origin, &(-scene.sun.dir), f32::INFINITY, time) {
+            visible += 1;
         }
     }
+    for light in &scene.lights {
+        let to_l = light.pos - hit.point;
+        let r = glm::length(&to_l).max(1e-6);
+        let l = to_l / r;
+        total += 1;
+        if !occluded(scene, &origin, &l, r - EPS, time) {
+            visible += 1;
+        }
+    }
+    if total == 0 {
+        1.0
+    } else {
+        visible as f32 / total as f32
+    }
 }
 
-pub fn render_to_fb(
-    cam: &Camera,
-    fb: &mut crate::framebuffer::FrameBuffer,
-    objects: &[Object],
-    skybox: Option<&crate::texture::Texture>,
-    lights: &[PointLight],
-) {
+fn debug_color(
+    ro: &glm::Vec3,
+    rd: &glm::Vec3,
+    scene: &Scene,
+    settings: &RenderSettings,
+    time: f32,
+) -> Rgb {
+    // Costo y rebotes miden el render sombreado completo del píxel.
+    if matches!(settings.mode, RenderMode::Cost | RenderMode::Bounces) {
+        let shaded = RenderSettings {
+            mode: RenderMode::Shaded,
+            ..*settings
+        };
+        RAY_TESTS.with(|c| c.set(0));
+        DEEPEST.with(|c| c.set(-1));
+        cast_ray(ro, rd, scene, &shaded, 0, time);
+        if settings.mode == RenderMode::Cost {
+            let tests = RAY_TESTS.with(|c| c.get()) as f32;
+            return heat_color((1.0 + tests).ln() / (1.0 + DEBUG_COST_MAX).ln());
+        }
+        let deepest = DEEPEST.with(|c| c.get());
+        let hit_something = scene_intersect(ro, rd, scene, time).is_intersecting;
+        if !hit_something {
+            return Rgb::new(0, 0, 0);
+        }
+        return heat_color((deepest + 1) as f32 / (MAX_DEPTH + 1) as f32);
+    }
+
+    let (index, mut hit) = scene_intersect_index(ro, rd, scene, time);
+    let Some(index) = index else {
+        return Rgb::new(0, 0, 0);
+    };
+    hit.normal = ripple_normal(&hit, settings.scene_time);
+    let unit = |v: f32| (v.clamp(0.0, 1.0) * 255.0) as u8;
+    match settings.mode {
+        RenderMode::Normals => Rgb::from_vec3(&(hit.normal * 0.5 + glm::vec3(0.5, 0.5, 0.5))),
+        RenderMode::Uv => match hit.uv {
+            Some((u, v)) => Rgb::new(unit(u.fract()), unit(v.fract()), 0),
+            None => Rgb::new(255, 0, 255),
+        },
+        RenderMode::Depth => {
+            let g = unit(1.0 - hit.distance / DEBUG_DEPTH_FAR);
+            Rgb::new(g, g, g)
+        }
+        RenderMode::MaterialId => {
+            let name = hit.material.name;
+            let h = name
+                .bytes()
+                .fold(2166136261u32, |h, b| (h ^ b as u32).wrapping_mul(16777619));
+            id_color(h)
+        }
+        RenderMode::ObjectId => id_color(index as u32),
+        RenderMode::Shadow => {
+            let g = unit(light_visibility(&hit, scene, time));
+            Rgb::new(g, g, g)
+        }
+        _ => Rgb::new(0, 0, 0),
+    }
+}
+
+// Render de las AOV con la cámara central (sin estéreo), promediando el obturador.
+pub fn render_aovs(cam: &Camera, aovs: &mut AovBuffer, scene: &Scene) {
+    let settings = &scene.settings;
+    let (w, h) = (aovs.width, aovs.height);
+    let samples = settings.motion_samples.max(1);
+    let mut shutter = Vec::with_capacity(samples as usize);
+    for y in 0..h {
+        for x in 0..w {
+            let mut rng = Rng::new(((y as u64) << 32 | x as u64) ^ settings.seed as u64);
+            shutter.clear();
+            for i in 0..samples {
+                let time = if samples == 1 {
+                    0.5
+                } else {
+                    (i as f32 + rng.next_f32()) / samples as f32
+                };
+                let c = cam.at_time(time);
+                if let Some((ro, rd)) = c.primary_ray(x, y, w, h) {
+                    shutter.push(trace_aov(&ro, &rd, scene, time));
+                }
+            }
+            aovs.set(x, y, AovSample::average(&shutter));
+        }
+    }
+}
+
+// Cada ojo ve su mitad de la imagen (o la imagen completa en anaglifo).
+fn eye_views(cam: &Camera, w: i32, h: i32) -> (i32, i32, Camera, Camera) {
+    let (eye_w, eye_h) = match cam.stereo {
+        StereoLayout::SideBySide => (w / 2, h),
+        StereoLayout::OverUnder => (w, h / 2),
+        _ => (w, h),
+    };
+    let aspect = eye_w as f32 / eye_h.max(1) as f32;
+    (eye_w, eye_h, cam.eye(false, aspect), cam.eye(true, aspect))
+}
+
+// Buffers de guía para el denoiser (albedo, normal y profundidad del rayo primario),
+// con la misma disposición estéreo que `render_to_fb`. En anaglifo se usa el ojo izquierdo.
+pub fn render_features(cam: &Camera, features: &mut AovBuffer, scene: &Scene) {
+    let settings = &scene.settings;
+    // Mitad del obturador, igual que el render con una sola muestra de tiempo.
+    let cam = &cam.at_time(0.5);
+    let (w, h) = (features.width, features.height);
+    let (eye_w, eye_h, left, right) = eye_views(cam, w, h);
+    for y in 0..h {
+        for x in 0..w {
+            let (c, ex, ey) = match cam.stereo {
+                StereoLayout::Off => (cam, x, y),
+                StereoLayout::SideBySide if x >= eye_w => (&right, x - eye_w, y),
+                StereoLayout::OverUnder if y >= eye_h => (&right, x, y - eye_h),
+                _ => (&left, x, y),
+            };
+            let mut s = AovSample::default();
+            if let Some((ro, rd)) = c.primary_ray(ex, ey, eye_w, eye_h) {
+                let mut hit = scene_intersect(&ro, &rd, scene, 0.5);
+                if hit.is_intersecting {
+                    hit.normal = ripple_normal(&hit, settings.scene_time);
+                    s.albedo = hit
+                        .material
+                        .sample_albedo(hit.uv, settings.scene_time)
+                        .to_vec3();
+                    s.normal = hit.normal;
+                    s.depth = hit.distance;
+                }
+            }
+            features.set(x, y, s);
+        }
+    }
+}
+
+pub fn render_to_fb(cam: &Camera, fb: &mut crate::framebuffer::FrameBuffer, scene: &Scene) {
+    let settings = &scene.settings;
     let w = fb.width;
     let h = fb.height;
 
     fb.clear();
 
+    let (eye_w, eye_h, left, right) = eye_views(cam, w, h);
+    let trace_at = |c: &Camera, x: i32, y: i32, time: f32| match c.primary_ray(x, y, eye_w, eye_h) {
+        Some((ro, rd)) => cast_ray(&ro, &rd, scene, settings, 0, time),
+        None => Rgb::new(0, 0, 0),
+    };
+    // Con motion blur se promedian instantes estratificados del obturador.
+    let samples = settings.motion_samples.max(1);
+    let trace = |c: &Camera, x: i32, y: i32| {
+        if samples == 1 {
+            return trace_at(&c.at_time(0.5), x, y, 0.5);
+        }
+        let mut rng = Rng::new(((y as u64) << 32 | x as u64) ^ settings.seed as u64);
+        let mut acc = glm::vec3(0.0, 0.0, 0.0);
+        for i in 0..samples {
+            let time = (i as f32 + rng.next_f32()) / samples as f32;
+            acc += trace_at(&c.at_time(time), x, y, time).to_vec3();
+        }
+        Rgb::from_vec3(&(acc / samples as f32))
+    };
+
     for y in 0..h {
         for x in 0..w {
-            let dir = cam.ray_dir(x, y, w, h);
-            let rgb: Rgb = cast_ray(&cam.pos, &dir, objects, 0, skybox, lights);
+            let rgb: Rgb = match cam.stereo {
+                StereoLayout::Off => trace(cam, x, y),
+                StereoLayout::SideBySide if x < eye_w => trace(&left, x, y),
+                StereoLayout::SideBySide => trace(&right, x - eye_w, y),
+                StereoLayout::OverUnder if y < eye_h => trace(&left, x, y),
+                StereoLayout::OverUnder => trace(&right, x, y - eye_h),
+                StereoLayout::Anaglyph => {
+                    let (l, r) = (trace(&left, x, y), trace(&right, x, y));
+                    Rgb::new(l.r, r.g, r.b)
+                }
+            };
+            fb.set_color(rgb);
             fb.set_pixel(x, y);
-            fb.set_color(rgb.to_raylib()); 
         }
     }
 }
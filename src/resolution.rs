@@ -0,0 +1,84 @@
+// Filtro para llevar el FB a la ventana.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Upscale {
+    Nearest,
+    Bilinear,
+    // Catmull-Rom (bicúbico): más nítido que el bilineal, con algo de halo en bordes.
+    Sharp,
+}
+
+impl Upscale {
+    const ALL: [(Upscale, &'static str); 3] = [
+        (Upscale::Nearest, "cercano"),
+        (Upscale::Bilinear, "bilineal"),
+        (Upscale::Sharp, "nitido"),
+    ];
+
+    pub fn from_name(name: &str) -> Option<Upscale> {
+        Self::ALL.iter().find(|(_, n)| *n == name).map(|(u, _)| *u)
+    }
+
+    pub fn name(self) -> &'static str {
+        Self::ALL
+            .iter()
+            .find(|(u, _)| *u == self)
+            .map_or("", |(_, n)| n)
+    }
+
+    pub fn next(self) -> Upscale {
+        let i = Self::ALL.iter().position(|(u, _)| *u == self).unwrap_or(0);
+        Self::ALL[(i + 1) % Self::ALL.len()].0
+    }
+}
+
+// Escalas posibles en pasos de 1/32 del tamaño de ventana: así el FB no se
+// reasigna en cada cuadro por variaciones mínimas.
+const SCALE_STEPS: f32 = 32.0;
+// Suavizado del controlador (fracción de la corrección que se aplica por cuadro).
+const RESPONSE: f32 = 0.2;
+
+// Resolución dinámica: ajusta la escala del FB respecto de la ventana para
+// mantener `target_fps`, entre `min_scale` y `max_scale`.
+#[derive(Clone, Copy, Debug)]
+pub struct DynamicResolution {
+    pub enabled: bool,
+    pub target_fps: f32,
+    pub min_scale: f32,
+    pub max_scale: f32,
+    pub scale: f32,
+}
+
+impl Default for DynamicResolution {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            target_fps: 30.0,
+            min_scale: 0.25,
+            max_scale: 1.0,
+            scale: 0.5,
+        }
+    }
+}
+
+impl DynamicResolution {
+    // `frame_time` en segundos del último cuadro renderizado a escala dinámica.
+    pub fn update(&mut self, frame_time: f32) {
+        if !self.enabled || frame_time <= 0.0 || self.target_fps <= 0.0 {
+            return;
+        }
+        // El costo crece con los píxeles (escala²): se corrige con la raíz del cociente.
+        let ideal = self.scale * (1.0 / (self.target_fps * frame_time)).sqrt();
+        self.scale += (ideal - self.scale) * RESPONSE;
+        self.scale = self
+            .scale
+            .clamp(self.min_scale, self.max_scale.max(self.min_scale));
+    }
+
+    pub fn size(&self, width: i32, height: i32) -> (i32, i32) {
+        let s = (self.scale * SCALE_STEPS).round().max(1.0) / SCALE_STEPS;
+        (
+            ((width as f32 * s).round() as i32).max(1),
+            ((height as f32 * s).round() as i32).max(1),
+        )
+    }
+}
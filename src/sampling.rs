@@ -0,0 +1,63 @@
+use glm::{Vec3, vec3};
+use nalgebra_glm as glm;
+
+// PCG pequeño: determinista por punto/píxel, sin dependencias externas.
+#[derive(Clone, Copy, Debug)]
+pub struct Rng {
+    state: u64,
+}
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        let mut rng = Self {
+            state: seed.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407),
+        };
+        rng.next_u32();
+        rng
+    }
+
+    pub fn from_point(p: &Vec3, seed: u32) -> Self {
+        let h = (p.x.to_bits() as u64).wrapping_mul(0x9E3779B97F4A7C15)
+            ^ (p.y.to_bits() as u64).wrapping_mul(0xC2B2AE3D27D4EB4F)
+            ^ (p.z.to_bits() as u64).wrapping_mul(0x165667B19E3779F9)
+            ^ seed as u64;
+        Self::new(h)
+    }
+
+    pub fn next_u32(&mut self) -> u32 {
+        let old = self.state;
+        self.state = old
+            .wrapping_mul(6364136223846793005)
+            .wrapping_add(1442695040888963407);
+        let xorshifted = (((old >> 18) ^ old) >> 27) as u32;
+        let rot = (old >> 59) as u32;
+        xorshifted.rotate_right(rot)
+    }
+
+    pub fn next_f32(&mut self) -> f32 {
+        (self.next_u32() >> 8) as f32 / (1u32 << 24) as f32
+    }
+}
+
+pub fn orthonormal_basis(n: &Vec3) -> (Vec3, Vec3) {
+    let a = if n.x.abs() > 0.9 {
+        vec3(0.0, 1.0, 0.0)
+    } else {
+        vec3(1.0, 0.0, 0.0)
+    };
+    let t = glm::normalize(&glm::cross(&a, n));
+    let b = glm::cross(n, &t);
+    (t, b)
+}
+
+// Dirección en el hemisferio +Z con densidad cos(theta)/pi.
+pub fn cosine_hemisphere(u1: f32, u2: f32) -> Vec3 {
+    let r = u1.sqrt();
+    let phi = 2.0 * std::f32::consts::PI * u2;
+    vec3(r * phi.cos(), r * phi.sin(), (1.0 - u1).max(0.0).sqrt())
+}
+
+pub fn to_world(local: &Vec3, n: &Vec3) -> Vec3 {
+    let (t, b) = orthonormal_basis(n);
+    t * local.x + b * local.y + n * local.z
+}
@@ -0,0 +1,215 @@
+// Todo lo que se renderiza: objetos, luces, cielo, niebla y ajustes. Los objetos van
+// en una BVH que se arma en `commit`; se cambian con `objects_mut` / `edit`, que la
+// marcan vieja, y mientras no se vuelva a armar se prueban todos, uno por uno.
+use crate::bvh::Bvh;
+use crate::color::Rgb;
+use crate::environment::Environment;
+use crate::intersect::{Intersect, RayIntersect};
+use crate::objects::Object;
+use crate::renderer::{DirectionalLight, PointLight, RenderSettings};
+use glm::Vec3;
+use nalgebra_glm as glm;
+use std::ops::ControlFlow;
+
+// Niebla exponencial: a distancia `d` se ve `1 - e^(-density * d)` del color de la niebla.
+// Con `density` = 0 no hay niebla.
+#[derive(Clone, Copy, Debug)]
+pub struct Fog {
+    pub color: Rgb,
+    pub density: f32,
+}
+
+impl Default for Fog {
+    fn default() -> Self {
+        Self {
+            color: Rgb::new(180, 190, 205),
+            density: 0.0,
+        }
+    }
+}
+
+impl Fog {
+    pub fn apply(&self, col: Rgb, distance: f32) -> Rgb {
+        if self.density <= 0.0 || !distance.is_finite() {
+            return col;
+        }
+        Rgb::lerp(col, self.color, 1.0 - (-self.density * distance).exp())
+    }
+}
+
+pub struct Scene {
+    objects: Vec<Object>,
+    pub lights: Vec<PointLight>,
+    pub sun: DirectionalLight,
+    pub environment: Option<Environment>,
+    pub fog: Fog,
+    // Luz ambiente sin IBL, como fracción del albedo.
+    pub ambient: f32,
+    pub settings: RenderSettings,
+    bvh: Bvh,
+    // Los objetos cambiaron desde que se armó `bvh`.
+    dirty: bool,
+}
+
+impl Scene {
+    pub fn builder() -> SceneBuilder {
+        SceneBuilder::default()
+    }
+
+    pub fn objects(&self) -> &[Object] {
+        &self.objects
+    }
+
+    // Acceso para mover, agregar o quitar objetos: la BVH queda vieja hasta `commit`.
+    pub fn objects_mut(&mut self) -> &mut Vec<Object> {
+        self.dirty = true;
+        &mut self.objects
+    }
+
+    // Objetos y luces a la vez (animación, editor), igual que `objects_mut`.
+    pub fn edit(&mut self) -> (&mut Vec<Object>, &mut Vec<PointLight>) {
+        self.dirty = true;
+        (&mut self.objects, &mut self.lights)
+    }
+
+    // Rearma la BVH si los objetos cambiaron desde la última vez.
+    pub fn commit(&mut self) {
+        if self.dirty {
+            self.bvh = Bvh::build(&self.objects);
+            self.dirty = false;
+        }
+    }
+
+    // Visita los objetos que el rayo puede tocar (ver `Bvh::traverse`). Si los objetos
+    // cambiaron desde el último `commit`, los recorre todos.
+    pub fn traverse(
+        &self,
+        ro: &Vec3,
+        rd: &Vec3,
+        t_max: f32,
+        mut visit: impl FnMut(usize, &Object) -> ControlFlow<(), f32>,
+    ) {
+        if self.dirty {
+            for (i, o) in self.objects.iter().enumerate() {
+                if visit(i, o).is_break() {
+                    return;
+                }
+            }
+            return;
+        }
+        self.bvh
+            .traverse(ro, rd, t_max, |i| visit(i, &self.objects[i]));
+    }
+
+    // Impacto más cercano y su índice en `objects`; a igual distancia gana el primero.
+    pub fn intersect(&self, ro: &Vec3, rd: &Vec3, time: f32) -> (Option<usize>, Intersect) {
+        let mut closest = Intersect::empty();
+        let mut index = None;
+        self.traverse(ro, rd, f32::INFINITY, |i, o| {
+            let hit = o.ray_intersect_at(ro, rd, time);
+            if hit.is_intersecting
+                && (hit.distance < closest.distance
+                    || (hit.distance == closest.distance && index.is_some_and(|j| i < j)))
+            {
+                closest = hit;
+                index = Some(i);
+            }
+            ControlFlow::Continue(closest.distance)
+        });
+        (index, closest)
+    }
+}
+
+pub struct SceneBuilder {
+    objects: Vec<Object>,
+    lights: Vec<PointLight>,
+    sun: Option<DirectionalLight>,
+    environment: Option<Environment>,
+    fog: Fog,
+    ambient: f32,
+    settings: RenderSettings,
+}
+
+impl Default for SceneBuilder {
+    fn default() -> Self {
+        Self {
+            objects: vec![],
+            lights: vec![],
+            sun: None,
+            environment: None,
+            fog: Fog::default(),
+            ambient: 0.12,
+            settings: RenderSettings::default(),
+        }
+    }
+}
+
+impl SceneBuilder {
+    pub fn with_object(mut self, object: Object) -> Self {
+        self.objects.push(object);
+        self
+    }
+
+    pub fn with_objects(mut self, objects: impl IntoIterator<Item = Object>) -> Self {
+        self.objects.extend(objects);
+        self
+    }
+
+    pub fn with_light(mut self, light: PointLight) -> Self {
+        self.lights.push(light);
+        self
+    }
+
+    pub fn with_lights(mut self, lights: impl IntoIterator<Item = PointLight>) -> Self {
+        self.lights.extend(lights);
+        self
+    }
+
+    // Sin sol explícito se usa el del cielo físico, o el de por defecto.
+    pub fn with_sun(mut self, sun: DirectionalLight) -> Self {
+        self.sun = Some(sun);
+        self
+    }
+
+    pub fn with_environment(mut self, environment: Environment) -> Self {
+        self.environment = Some(environment);
+        self
+    }
+
+    pub fn with_fog(mut self, fog: Fog) -> Self {
+        self.fog = fog;
+        self
+    }
+
+    pub fn with_ambient(mut self, ambient: f32) -> Self {
+        self.ambient = ambient;
+        self
+    }
+
+    pub fn with_settings(mut self, settings: RenderSettings) -> Self {
+        self.settings = settings;
+        self
+    }
+
+    pub fn build(self) -> Scene {
+        let sun = self.sun.unwrap_or_else(|| {
+            self.environment
+                .as_ref()
+                .and_then(DirectionalLight::from_sky)
+                .unwrap_or_default()
+        });
+        let mut scene = Scene {
+            objects: self.objects,
+            lights: self.lights,
+            sun,
+            environment: self.environment,
+            fog: self.fog,
+            ambient: self.ambient,
+            settings: self.settings,
+            bvh: Bvh::default(),
+            dirty: true,
+        };
+        scene.commit();
+        scene
+    }
+}
@@ -0,0 +1,166 @@
+use glm::{Vec3, vec3};
+use nalgebra_glm as glm;
+use std::f32::consts::PI;
+
+// Exposición que lleva la luminancia de Preetham (kcd/m²) a valores de pantalla.
+const EXPOSURE: f32 = 0.03;
+// Inclinación de la trayectoria del sol hacia +Z (de frente a la casa).
+const SUN_PATH_TILT_DEG: f32 = 25.0;
+const SUN_DISK_COS: f32 = 0.99996; // ~0.5°
+const NIGHT_COLOR: [f32; 3] = [0.012, 0.018, 0.045];
+
+// Cielo analítico de Preetham et al. (1999), iluminado por el sol según la hora.
+#[derive(Clone, Debug)]
+pub struct PhysicalSky {
+    pub turbidity: f32,
+    pub hours: f32,
+    sun_dir: Vec3,
+}
+
+struct Perez {
+    a: f32,
+    b: f32,
+    c: f32,
+    d: f32,
+    e: f32,
+}
+
+impl Perez {
+    fn eval(&self, cos_theta: f32, gamma: f32) -> f32 {
+        (1.0 + self.a * (self.b / cos_theta.max(0.01)).exp())
+            * (1.0 + self.c * (self.d * gamma).exp() + self.e * gamma.cos().powi(2))
+    }
+}
+
+fn smoothstep(e0: f32, e1: f32, x: f32) -> f32 {
+    let t = ((x - e0) / (e1 - e0)).clamp(0.0, 1.0);
+    t * t * (3.0 - 2.0 * t)
+}
+
+impl PhysicalSky {
+    pub fn new(turbidity: f32, hours: f32) -> Self {
+        let mut sky = Self {
+            turbidity,
+            hours,
+            sun_dir: vec3(0.0, 1.0, 0.0),
+        };
+        sky.set_time_of_day(hours);
+        sky
+    }
+
+    // 6h amanecer, 12h mediodía, 18h atardecer; de noche el sol queda bajo el horizonte.
+    pub fn set_time_of_day(&mut self, hours: f32) {
+        self.hours = hours.rem_euclid(24.0);
+        let t = (self.hours - 6.0) / 12.0 * PI;
+        let tilt = SUN_PATH_TILT_DEG.to_radians();
+        self.sun_dir = glm::normalize(&vec3(t.cos(), t.sin() * tilt.cos(), t.sin() * tilt.sin()));
+    }
+
+    // Dirección hacia el sol.
+    pub fn sun_dir(&self) -> Vec3 {
+        self.sun_dir
+    }
+
+    fn day_factor(&self) -> f32 {
+        smoothstep(-0.25, 0.05, self.sun_dir.y)
+    }
+
+    // Color e intensidad de la luz direccional del sol (0 de noche).
+    pub fn sun_light(&self) -> (Vec3, f32) {
+        let elev = self.sun_dir.y;
+        let warm = smoothstep(0.0, 0.35, elev);
+        let low = vec3(1.0, 0.45, 0.2);
+        let high = vec3(1.0, 0.96, 0.9);
+        (
+            low * (1.0 - warm) + high * warm,
+            smoothstep(-0.03, 0.08, elev),
+        )
+    }
+
+    // Fondo visible: cielo + disco solar.
+    pub fn radiance(&self, dir: &Vec3) -> Vec3 {
+        let d = glm::normalize(dir);
+        let mut col = self.sky_radiance(&d);
+        if d.y >= 0.0 && glm::dot(&d, &self.sun_dir) > SUN_DISK_COS && self.sun_dir.y > -0.02 {
+            let (sun_col, sun_i) = self.sun_light();
+            col += sun_col * (20.0 * sun_i * self.day_factor());
+        }
+        col
+    }
+
+    // Solo el cielo: el disco solar ya lo aporta la luz direccional en `shade`.
+    pub fn sky_radiance(&self, dir: &Vec3) -> Vec3 {
+        let d = glm::normalize(dir);
+        let night = vec3(NIGHT_COLOR[0], NIGHT_COLOR[1], NIGHT_COLOR[2]);
+        let day = self.day_factor();
+        if day <= 0.0 {
+            return night;
+        }
+        // Bajo el horizonte se repite el color del horizonte, oscurecido.
+        if d.y < 0.0 {
+            let horizon = glm::normalize(&vec3(d.x, 0.0, d.z));
+            return self.preetham(&horizon) * (0.35 * day) + night;
+        }
+        self.preetham(&d) * day + night
+    }
+
+    fn preetham(&self, d: &Vec3) -> Vec3 {
+        let t = self.turbidity;
+        // El modelo no vale con el sol bajo el horizonte: se congela justo encima.
+        let theta_s = self.sun_dir.y.clamp(0.02, 1.0).asin();
+        let theta_s = PI * 0.5 - theta_s;
+        let cos_theta = d.y.max(0.0);
+        let gamma = glm::dot(d, &self.sun_dir).clamp(-1.0, 1.0).acos();
+
+        let py = Perez {
+            a: 0.1787 * t - 1.4630,
+            b: -0.3554 * t + 0.4275,
+            c: -0.0227 * t + 5.3251,
+            d: 0.1206 * t - 2.5771,
+            e: -0.0670 * t + 0.3703,
+        };
+        let px = Perez {
+            a: -0.0193 * t - 0.2592,
+            b: -0.0665 * t + 0.0008,
+            c: -0.0004 * t + 0.2125,
+            d: -0.0641 * t - 0.8989,
+            e: -0.0033 * t + 0.0452,
+        };
+        let ppy = Perez {
+            a: -0.0167 * t - 0.2608,
+            b: -0.0950 * t + 0.0092,
+            c: -0.0079 * t + 0.2102,
+            d: -0.0441 * t - 1.6537,
+            e: -0.0109 * t + 0.0529,
+        };
+
+        let chi = (4.0 / 9.0 - t / 120.0) * (PI - 2.0 * theta_s);
+        let zenith_y = (4.0453 * t - 4.9710) * chi.tan() - 0.2155 * t + 2.4192;
+        let (t2, t3) = (theta_s * theta_s, theta_s * theta_s * theta_s);
+        let zenith_x = t * t * (0.00166 * t3 - 0.00375 * t2 + 0.00209 * theta_s)
+            + t * (-0.02903 * t3 + 0.06377 * t2 - 0.03202 * theta_s + 0.00394)
+            + (0.11693 * t3 - 0.21196 * t2 + 0.06052 * theta_s + 0.25886);
+        let zenith_yc = t * t * (0.00275 * t3 - 0.00610 * t2 + 0.00317 * theta_s)
+            + t * (-0.04214 * t3 + 0.08970 * t2 - 0.04153 * theta_s + 0.00516)
+            + (0.15346 * t3 - 0.26756 * t2 + 0.06670 * theta_s + 0.26688);
+
+        let lum = zenith_y * py.eval(cos_theta, gamma) / py.eval(1.0, theta_s);
+        let x = zenith_x * px.eval(cos_theta, gamma) / px.eval(1.0, theta_s);
+        let y = zenith_yc * ppy.eval(cos_theta, gamma) / ppy.eval(1.0, theta_s);
+
+        xyy_to_display(x, y, lum.max(0.0) * EXPOSURE)
+    }
+}
+
+fn xyy_to_display(x: f32, y: f32, lum: f32) -> Vec3 {
+    if y <= 0.0 {
+        return vec3(0.0, 0.0, 0.0);
+    }
+    let cx = x / y * lum;
+    let cz = (1.0 - x - y) / y * lum;
+    let r = 3.2406 * cx - 1.5372 * lum - 0.4986 * cz;
+    let g = -0.9689 * cx + 1.8758 * lum + 0.0415 * cz;
+    let b = 0.0557 * cx - 0.2040 * lum + 1.0570 * cz;
+    let gamma = |v: f32| v.max(0.0).powf(1.0 / 2.2);
+    vec3(gamma(r), gamma(g), gamma(b))
+}
@@ -0,0 +1,169 @@
+use crate::aov::AovBuffer;
+use crate::camera::{Camera, Projection, StereoLayout};
+use crate::color::Rgb;
+use crate::framebuffer::FrameBuffer;
+use glm::{Vec3, vec3};
+use nalgebra_glm as glm;
+
+// Distancia a la que se ubica el cielo para reproyectarlo (solo importa la dirección).
+const SKY_DISTANCE: f32 = 1e4;
+
+// Secuencia de Halton en base `b`, en [0, 1).
+fn halton(mut i: u32, b: u32) -> f32 {
+    let (mut f, mut r) = (1.0, 0.0);
+    while i > 0 {
+        f /= b as f32;
+        r += f * (i % b) as f32;
+        i /= b;
+    }
+    r
+}
+
+// Reproyección temporal: mezcla el cuadro nuevo con el anterior llevado a la vista
+// actual. Con jitter subpíxel da antialias y baja el ruido al moverse.
+pub struct Temporal {
+    pub enabled: bool,
+    // Peso del cuadro nuevo; más bajo = más suave, pero más estela.
+    pub blend: f32,
+    // Diferencia relativa de profundidad a partir de la cual el historial se descarta.
+    pub depth_tolerance: f32,
+    frame: u32,
+    width: i32,
+    height: i32,
+    history: Vec<Vec3>,
+    history_depth: Vec<f32>,
+    prev_cam: Option<Camera>,
+}
+
+impl Default for Temporal {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            blend: 0.2,
+            depth_tolerance: 0.05,
+            frame: 0,
+            width: 0,
+            height: 0,
+            history: vec![],
+            history_depth: vec![],
+            prev_cam: None,
+        }
+    }
+}
+
+impl Temporal {
+    pub fn reset(&mut self) {
+        self.prev_cam = None;
+    }
+
+    // Jitter del próximo cuadro (Halton 2,3), en píxeles alrededor del centro.
+    pub fn next_jitter(&mut self) -> (f32, f32) {
+        self.frame = self.frame % 16 + 1;
+        (halton(self.frame, 2) - 0.5, halton(self.frame, 3) - 0.5)
+    }
+
+    // Solo perspectiva y orto sin estéreo tienen `Camera::project`.
+    fn supported(cam: &Camera) -> bool {
+        cam.stereo == StereoLayout::Off
+            && matches!(
+                cam.projection,
+                Projection::Perspective | Projection::Orthographic
+            )
+    }
+
+    fn history_at(&self, x: i32, y: i32) -> (Vec3, f32) {
+        let i = (y.clamp(0, self.height - 1) * self.width + x.clamp(0, self.width - 1)) as usize;
+        (self.history[i], self.history_depth[i])
+    }
+
+    // Historial bilineal en coordenadas continuas de píxel.
+    fn sample_history(&self, fx: f32, fy: f32) -> Vec3 {
+        let (x0, y0) = ((fx - 0.5).floor(), (fy - 0.5).floor());
+        let (tx, ty) = (fx - 0.5 - x0, fy - 0.5 - y0);
+        let (x0, y0) = (x0 as i32, y0 as i32);
+        let top = glm::lerp(
+            &self.history_at(x0, y0).0,
+            &self.history_at(x0 + 1, y0).0,
+            tx,
+        );
+        let bottom = glm::lerp(
+            &self.history_at(x0, y0 + 1).0,
+            &self.history_at(x0 + 1, y0 + 1).0,
+            tx,
+        );
+        glm::lerp(&top, &bottom, ty)
+    }
+
+    // `features` debe venir de `renderer::render_features` con la misma cámara (y jitter)
+    // con la que se renderizó `fb`.
+    pub fn apply(&mut self, cam: &Camera, fb: &mut FrameBuffer, features: &AovBuffer) {
+        let (w, h) = (fb.width, fb.height);
+        let data = &fb.pixels;
+        if features.width != w || features.height != h || data.len() < (w * h) as usize {
+            return;
+        }
+        let current: Vec<Vec3> = data
+            .iter()
+            .map(|c| vec3(c.r as f32, c.g as f32, c.b as f32) / 255.0)
+            .collect();
+        let depth: Vec<f32> = features.pixels.iter().map(|p| p.depth).collect();
+
+        let usable = Self::supported(cam) && self.width == w && self.height == h;
+        let prev = self.prev_cam.as_ref().filter(|_| usable);
+        let mut out = current.clone();
+        if let Some(prev) = prev {
+            for y in 0..h {
+                for x in 0..w {
+                    let i = (y * w + x) as usize;
+                    let Some((ro, rd)) = cam.primary_ray(x, y, w, h) else {
+                        continue;
+                    };
+                    let d = depth[i];
+                    let world = ro + rd * if d.is_finite() { d } else { SKY_DISTANCE };
+                    let Some((hx, hy, hd)) = prev.project(&world, w, h) else {
+                        continue;
+                    };
+                    // `project` no lleva jitter, pero el historial se renderizó con el del
+                    // cuadro anterior: su píxel i está centrado en i + 0.5 + jitter.
+                    let (hx, hy) = (hx - prev.jitter.0, hy - prev.jitter.1);
+                    if hx < 0.0 || hy < 0.0 || hx >= w as f32 || hy >= h as f32 {
+                        continue;
+                    }
+                    // Desoclusión: lo que había en ese píxel estaba a otra distancia.
+                    let (_, prev_d) = self.history_at(hx as i32, hy as i32);
+                    let occluded = if d.is_finite() {
+                        !prev_d.is_finite()
+                            || (prev_d - hd).abs() > self.depth_tolerance * hd.max(1e-3)
+                    } else {
+                        prev_d.is_finite()
+                    };
+                    if occluded {
+                        continue;
+                    }
+                    // El historial se recorta a la vecindad 3x3 actual (menos estela).
+                    let (mut lo, mut hi) = (current[i], current[i]);
+                    for ny in (y - 1).max(0)..=(y + 1).min(h - 1) {
+                        for nx in (x - 1).max(0)..=(x + 1).min(w - 1) {
+                            let c = current[(ny * w + nx) as usize];
+                            lo = glm::min2(&lo, &c);
+                            hi = glm::max2(&hi, &c);
+                        }
+                    }
+                    let hist = glm::clamp_vec(&self.sample_history(hx, hy), &lo, &hi);
+                    out[i] = glm::lerp(&hist, &current[i], self.blend);
+                }
+            }
+        }
+
+        for y in 0..h {
+            for x in 0..w {
+                fb.set_pixel_color(x, y, Rgb::from_vec3(&out[(y * w + x) as usize]));
+            }
+        }
+        self.history = out;
+        self.history_depth = depth;
+        self.width = w;
+        self.height = h;
+        self.prev_cam = Some(cam.clone());
+    }
+}
@@ -1,42 +1,187 @@
 use crate::color::Rgb;
-use raylib::prelude::*;
+use crate::error::Error;
+#[cfg(not(feature = "raylib"))]
+use crate::formats::png;
 use nalgebra_glm as glm;
 use glm::Vec3;
+#[cfg(feature = "raylib")]
+use raylib::prelude::*;
 
+// `w`/`h` son de un cuadro; las animadas guardan `frames` cuadros apilados en `pixels`.
 #[derive(Debug, Clone)]
 pub struct Texture {
     pub w: i32,
     pub h: i32,
     pub pixels: Vec<Rgb>,
+    pub frames: i32,
+    pub fps: f32,
 }
 impl Texture {
-    pub fn from_file(path: &str) -> Self {
-        let img = Image::load_image(path).expect("No pude cargar la textura");
-        let w = img.width();
-        let h = img.height();
-        let data = img.get_image_data();
-        let mut pixels = Vec::with_capacity((w * h) as usize);
-        for c in data.iter() {
-            pixels.push(Rgb::new(c.r, c.g, c.b));
-        }
-        Self { w, h, pixels }
-    }
-    pub fn from_file_maybe(path: &str) -> Option<Self> {
-        if let Ok(img) = Image::load_image(path) {
-            let w = img.width();
-            let h = img.height();
-            let data = img.get_image_data();
-            let mut pixels = Vec::with_capacity((w * h) as usize);
-            for c in data.iter() {
-                pixels.push(Rgb::new(c.r, c.g, c.b));
+    // Cualquier formato que lea raylib (PNG, JPG, BMP, TGA...); la transparencia se descarta.
+    #[cfg(feature = "raylib")]
+    fn load_image(path: &str) -> Result<Self, Error> {
+        let img = Image::load_image(path).map_err(|e| Error::load(path, e))?;
+        Ok(Self {
+            w: img.width(),
+            h: img.height(),
+            pixels: img
+                .get_image_data()
+                .iter()
+                .map(|c| Rgb::new(c.r, c.g, c.b))
+                .collect(),
+            frames: 1,
+            fps: 0.0,
+        })
+    }
+    // Sin raylib (render headless) solo PNG, con el decodificador propio.
+    #[cfg(not(feature = "raylib"))]
+    fn load_image(path: &str) -> Result<Self, Error> {
+        let bytes = std::fs::read(path).map_err(|e| Error::read(path, e))?;
+        let (w, h, rgba) = png::decode(&bytes).map_err(|e| Error::format(path, e))?;
+        Ok(Self {
+            w: w as i32,
+            h: h as i32,
+            pixels: rgba.iter().map(|p| Rgb::new(p[0], p[1], p[2])).collect(),
+            frames: 1,
+            fps: 0.0,
+        })
+    }
+    // Si hay un `.mcmeta` al lado (estilo Minecraft), la imagen es una tira animada.
+    pub fn from_file(path: &str) -> Result<Self, Error> {
+        let tex = Self::load_image(path)?;
+        Ok(match std::fs::read_to_string(format!("{path}.mcmeta")) {
+            Ok(meta) => tex.into_strip(20.0 / mcmeta_frametime(&meta)),
+            Err(_) => tex,
+        })
+    }
+    // Tira vertical de cuadros cuadrados (alto = ancho * cuadros).
+    pub fn from_strip(path: &str, fps: f32) -> Result<Self, Error> {
+        Ok(Self::load_image(path)?.into_strip(fps))
+    }
+    // Damero magenta/negro para las texturas que no se pudieron cargar (2x2 casillas por cara).
+    pub fn missing() -> Self {
+        let (size, cell) = (8, 4);
+        let pixels = (0..size * size)
+            .map(|i| {
+                let (x, y) = (i % size, i / size);
+                if (x / cell + y / cell) % 2 == 0 {
+                    Rgb::new(255, 0, 255)
+                } else {
+                    Rgb::new(0, 0, 0)
+                }
+            })
+            .collect();
+        Self {
+            w: size,
+            h: size,
+            pixels,
+            frames: 1,
+            fps: 0.0,
+        }
+    }
+    fn into_strip(mut self, fps: f32) -> Self {
+        if self.h > self.w && self.h % self.w == 0 {
+            self.frames = self.h / self.w;
+            self.h = self.w;
+            self.fps = fps;
+        }
+        self
+    }
+    // Cuadro que toca en `time` segundos (se repite en bucle).
+    pub fn frame_at(&self, time: f32) -> i32 {
+        if self.frames <= 1 || self.fps <= 0.0 {
+            return 0;
+        }
+        ((time * self.fps).floor() as i64).rem_euclid(self.frames as i64) as i32
+    }
+    pub fn sample_at(&self, u: f32, v: f32, time: f32) -> Rgb {
+        self.sample_frame(u, v, self.frame_at(time))
+    }
+    #[inline]
+    pub fn sample_repeat(&self, u: f32, v: f32) -> Rgb {
+        self.sample_frame(u, v, 0)
+    }
+    #[inline]
+    fn sample_frame(&self, mut u: f32, mut v: f32, frame: i32) -> Rgb {
+        u = u.fract();
+        if u < 0.0 {
+            u += 1.0;
+        }
+        v = v.fract();
+        if v < 0.0 {
+            v += 1.0;
+        }
+        let x = (u * self.w as f32) as i32;
+        let y = ((1.0 - v) * self.h as f32) as i32;
+        let xi = x.clamp(0, self.w - 1);
+        let yi = y.clamp(0, self.h - 1) + frame * self.h;
+        self.pixels[(yi * self.w + xi) as usize]
+    }
+    pub fn sample_dir_equirect(&self, dir: &glm::Vec3) -> Rgb {
+        let d = glm::normalize(dir);
+        let u = 0.5 + d.z.atan2(d.x) / (2.0 * std::f32::consts::PI);
+        let v = 0.5 - d.y.asin() / std::f32::consts::PI;
+        self.sample_repeat(u, v)
+    }
+}
+
+// `"frametime": N` del .mcmeta, en ticks de 1/20 s (1 si no aparece).
+fn mcmeta_frametime(meta: &str) -> f32 {
+    meta.split("\"frametime\"")
+        .nth(1)
+        .and_then(|rest| {
+            let digits: String = rest
+                .trim_start_matches(|c: char| c == ':' || c.is_whitespace())
+                .chars()
+                .take_while(|c| c.is_ascii_digit())
+                .collect();
+            digits.parse::<f32>().ok()
+        })
+        .unwrap_or(1.0)
+        .max(1.0)
+}
+
+// Textura en flotante (HDR). Misma convención de filas/UV que `Texture`.
+#[derive(Debug, Clone)]
+pub struct HdrTexture {
+    pub w: i32,
+    pub h: i32,
+    pub pixels: Vec<Vec3>,
+}
+impl HdrTexture {
+    pub fn from_file(path: &str) -> Result<Self, Error> {
+        let ext = std::path::Path::new(path)
+            .extension()
+            .and_then(|e| e.to_str())
+            .map(|e| e.to_ascii_lowercase())
+            .unwrap_or_default();
+        let decoded = match ext.as_str() {
+            "hdr" | "pic" => {
+                let bytes = std::fs::read(path).map_err(|e| Error::read(path, e))?;
+                crate::formats::rgbe::decode(&bytes)
+            }
+            "exr" => {
+                let bytes = std::fs::read(path).map_err(|e| Error::read(path, e))?;
+                crate::formats::exr::decode(&bytes)
             }
-            Some(Self { w, h, pixels })
-        } else {
-            None
+            _ => return Ok(Self::from_ldr(&Texture::load_image(path)?)),
+        };
+        let (w, h, pixels) = decoded.map_err(|e| Error::format(path, e))?;
+        Ok(Self {
+            w: w as i32,
+            h: h as i32,
+            pixels,
+        })
+    }
+    pub fn from_ldr(tex: &Texture) -> Self {
+        Self {
+            w: tex.w,
+            h: tex.h,
+            pixels: tex.pixels.iter().map(|c| c.to_vec3()).collect(),
         }
     }
     #[inline]
-    pub fn sample_repeat(&self, mut u: f32, mut v: f32) -> Rgb {
+    pub fn sample_repeat(&self, mut u: f32, mut v: f32) -> Vec3 {
         u = u.fract();
         if u < 0.0 {
             u += 1.0;
@@ -51,10 +196,66 @@ impl Texture {
         let yi = y.clamp(0, self.h - 1);
         self.pixels[(yi * self.w + xi) as usize]
     }
-    pub fn sample_dir_equirect(&self, dir: &glm::Vec3) -> Rgb {
+    pub fn sample_dir_equirect(&self, dir: &glm::Vec3) -> Vec3 {
         let d = glm::normalize(dir);
         let u = 0.5 + d.z.atan2(d.x) / (2.0 * std::f32::consts::PI);
         let v = 0.5 - d.y.asin() / std::f32::consts::PI;
         self.sample_repeat(u, v)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Tira de `frames` cuadros de 2x2, cada cuadro de un gris distinto.
+    fn strip(frames: i32) -> Texture {
+        Texture {
+            w: 2,
+            h: 2 * frames,
+            pixels: (0..4 * frames)
+                .map(|i| {
+                    let g = (i / 4) as u8 * 10;
+                    Rgb::new(g, g, g)
+                })
+                .collect(),
+            frames: 1,
+            fps: 0.0,
+        }
+    }
+
+    #[test]
+    fn tira_en_cuadros() {
+        let tex = strip(4).into_strip(8.0);
+        assert_eq!((tex.w, tex.h, tex.frames, tex.fps), (2, 2, 4, 8.0));
+        assert_eq!(tex.sample_at(0.5, 0.5, 0.0), Rgb::new(0, 0, 0));
+        assert_eq!(tex.sample_at(0.5, 0.5, 0.3), Rgb::new(20, 20, 20));
+
+        // Alto que no es múltiplo del ancho: queda como imagen fija.
+        let mut odd = strip(2);
+        odd.h = 3;
+        let odd = odd.into_strip(8.0);
+        assert_eq!((odd.h, odd.frames), (3, 1));
+    }
+
+    #[test]
+    fn cuadro_en_bucle() {
+        let tex = strip(4).into_strip(2.0);
+        assert_eq!(tex.frame_at(0.0), 0);
+        assert_eq!(tex.frame_at(0.6), 1);
+        assert_eq!(tex.frame_at(2.0), 0);
+        assert_eq!(tex.frame_at(2.5), 1);
+        assert_eq!(tex.frame_at(-0.2), 3);
+        assert_eq!(strip(4).frame_at(10.0), 0);
+        assert_eq!(strip(4).into_strip(0.0).frame_at(10.0), 0);
+    }
+
+    #[test]
+    fn frametime_del_mcmeta() {
+        assert_eq!(mcmeta_frametime(r#"{"animation": {"frametime": 4}}"#), 4.0);
+        assert_eq!(mcmeta_frametime("{\"frametime\" :\n 12 }"), 12.0);
+        assert_eq!(mcmeta_frametime(r#"{"animation": {}}"#), 1.0);
+        assert_eq!(mcmeta_frametime(r#"{"frametime": 0}"#), 1.0);
+        assert_eq!(mcmeta_frametime(r#"{"frametime": "x"}"#), 1.0);
+    }
+}
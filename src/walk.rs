@@ -0,0 +1,208 @@
+use crate::camera::Camera;
+use crate::intersect::{Intersect, RayIntersect};
+use crate::objects::Object;
+use glm::{Vec3, vec3};
+use nalgebra_glm as glm;
+
+const UP: Vec3 = Vec3::new(0.0, 1.0, 0.0);
+// Por debajo de esta altura se considera que el jugador cayó fuera de la plataforma.
+const FALL_LIMIT: f32 = -20.0;
+
+// Modo caminata: el jugador es una cápsula vertical con los ojos en `cam.pos`.
+// Las colisiones se resuelven con rayos contra la escena (el mismo `ray_intersect`).
+#[derive(Clone, Debug)]
+pub struct Walker {
+    pub enabled: bool,
+    pub radius: f32,
+    pub eye_height: f32,
+    // Altura máxima que se sube sin saltar (un bloque).
+    pub step_height: f32,
+    pub gravity: f32,
+    pub jump_speed: f32,
+    // Fracción de la velocidad de vuelo que se usa al caminar.
+    pub speed_scale: f32,
+    velocity_y: f32,
+    on_ground: bool,
+    spawn: Vec3,
+}
+
+impl Default for Walker {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            radius: 0.15,
+            eye_height: 0.9,
+            step_height: 0.42,
+            gravity: 9.0,
+            jump_speed: 3.2,
+            speed_scale: 0.4,
+            velocity_y: 0.0,
+            on_ground: false,
+            spawn: vec3(0.0, 0.0, 0.0),
+        }
+    }
+}
+
+fn cast(objects: &[Object], ro: &Vec3, rd: &Vec3, max: f32) -> Option<Intersect> {
+    let mut closest: Option<Intersect> = None;
+    for o in objects {
+        let hit = o.ray_intersect(ro, rd);
+        if hit.is_intersecting
+            && hit.distance >= 0.0
+            && hit.distance < max
+            && closest.as_ref().is_none_or(|c| hit.distance < c.distance)
+        {
+            closest = Some(hit);
+        }
+    }
+    closest
+}
+
+impl Walker {
+    pub fn toggle(&mut self, cam: &Camera) {
+        self.enabled = !self.enabled;
+        self.velocity_y = 0.0;
+        self.on_ground = false;
+        self.spawn = cam.pos;
+    }
+
+    // `wish` viene de `Camera::move_input` (x derecha, z adelante); la altura la manda la gravedad.
+    pub fn update(
+        &mut self,
+        cam: &mut Camera,
+        wish: &Vec3,
+        jump: bool,
+        dt: f32,
+        objects: &[Object],
+    ) {
+        let fwd = glm::normalize(&vec3(cam.forward.x, 0.0, cam.forward.z));
+        let right = glm::cross(&fwd, &UP);
+        let delta = (fwd * wish.z + right * wish.x) * self.speed_scale;
+
+        let mut feet = cam.pos - UP * self.eye_height;
+        feet = self.move_horizontal(feet, delta, objects);
+        feet = self.push_out(feet, objects);
+
+        if self.on_ground && jump {
+            self.velocity_y = self.jump_speed;
+            self.on_ground = false;
+        }
+        self.velocity_y -= self.gravity * dt;
+        let dy = self.velocity_y * dt;
+
+        if dy > 0.0 {
+            // Techo: se corta el salto al tocar con la cabeza.
+            let head = feet + UP * self.eye_height;
+            if let Some(hit) = cast(objects, &head, &UP, dy + 0.05) {
+                feet.y += (hit.distance - 0.05).max(0.0);
+                self.velocity_y = 0.0;
+            } else {
+                feet.y += dy;
+            }
+            self.on_ground = false;
+        } else {
+            // El suelo se busca desde la altura de un escalón: así se suben bloques sueltos.
+            match self.ground_height(&feet, objects) {
+                Some(floor) if feet.y + dy <= floor + 1e-3 => {
+                    feet.y = floor;
+                    self.velocity_y = 0.0;
+                    self.on_ground = true;
+                }
+                _ => {
+                    feet.y += dy;
+                    self.on_ground = false;
+                }
+            }
+        }
+
+        cam.pos = feet + UP * self.eye_height;
+        if feet.y < FALL_LIMIT {
+            // Cayó al vacío: vuelve a donde empezó a caminar, en modo vuelo.
+            cam.pos = self.spawn;
+            self.enabled = false;
+            self.velocity_y = 0.0;
+        }
+    }
+
+    // Alturas de los rayos laterales: justo sobre el escalón, la cintura y los ojos.
+    fn probe_heights(&self) -> [f32; 3] {
+        let low = self.step_height + 0.02;
+        [low, (low + self.eye_height) * 0.5, self.eye_height]
+    }
+
+    // Avanza hasta chocar y desliza a lo largo de la pared (hasta 3 choques por cuadro).
+    fn move_horizontal(&self, mut feet: Vec3, mut delta: Vec3, objects: &[Object]) -> Vec3 {
+        for _ in 0..3 {
+            let len = glm::length(&delta);
+            if len < 1e-6 {
+                break;
+            }
+            let dir = delta / len;
+            let side = glm::cross(&dir, &UP) * (self.radius * 0.9);
+            let mut nearest: Option<(f32, Vec3)> = None;
+            for h in self.probe_heights() {
+                for offset in [vec3(0.0, 0.0, 0.0), side, -side] {
+                    let ro = feet + UP * h + offset;
+                    if let Some(hit) = cast(objects, &ro, &dir, len + self.radius)
+                        && nearest.is_none_or(|(d, _)| hit.distance < d)
+                    {
+                        nearest = Some((hit.distance, hit.normal));
+                    }
+                }
+            }
+            let Some((dist, normal)) = nearest else {
+                feet += delta;
+                break;
+            };
+            let allowed = (dist - self.radius).clamp(0.0, len);
+            feet += dir * allowed;
+            let n = vec3(normal.x, 0.0, normal.z);
+            if glm::length(&n) < 1e-4 {
+                break;
+            }
+            let n = glm::normalize(&n);
+            let rest = delta - dir * allowed;
+            delta = rest - n * glm::dot(&rest, &n);
+        }
+        feet
+    }
+
+    // Si quedó metido en un bloque (p. ej. al activar el modo), lo empuja hacia afuera.
+    fn push_out(&self, mut feet: Vec3, objects: &[Object]) -> Vec3 {
+        let dirs = [
+            vec3(1.0, 0.0, 0.0),
+            vec3(-1.0, 0.0, 0.0),
+            vec3(0.0, 0.0, 1.0),
+            vec3(0.0, 0.0, -1.0),
+        ];
+        for h in self.probe_heights() {
+            for dir in dirs.iter() {
+                let ro = feet + UP * h;
+                if let Some(hit) = cast(objects, &ro, dir, self.radius) {
+                    feet -= dir * (self.radius - hit.distance);
+                }
+            }
+        }
+        feet
+    }
+
+    // Altura del suelo más alto bajo la cápsula, si está a menos de un escalón por encima.
+    fn ground_height(&self, feet: &Vec3, objects: &[Object]) -> Option<f32> {
+        let r = self.radius * 0.7;
+        let offsets = [
+            vec3(0.0, 0.0, 0.0),
+            vec3(r, 0.0, 0.0),
+            vec3(-r, 0.0, 0.0),
+            vec3(0.0, 0.0, r),
+            vec3(0.0, 0.0, -r),
+        ];
+        let down = -UP;
+        offsets
+            .iter()
+            .filter_map(|o| {
+                let ro = feet + o + UP * self.step_height;
+                cast(objects, &ro, &down, f32::INFINITY).map(|hit| hit.point.y)
+            })
+            .reduce(f32::max)
+    }
+}
@@ -0,0 +1,303 @@
+// Pruebas de regresión por imagen: escenas chicas renderizadas sin ventana y comparadas
+// contra las de `tests/golden/` (PPM binario). Si una falla, en `target/regresion/`
+// quedan la imagen obtenida y un diff (rojo = píxel distinto).
+// `BLESS=1 cargo test regresion` reescribe las de referencia con el render actual.
+use glm::vec3;
+use nalgebra_glm as glm;
+use proyect2_raytracing::camera::{Camera, Projection};
+use proyect2_raytracing::color::{Material, Rgb};
+use proyect2_raytracing::environment::{EnvSource, Environment};
+use proyect2_raytracing::framebuffer::FrameBuffer;
+use proyect2_raytracing::objects::{Cube, Object, Plane};
+use proyect2_raytracing::renderer::{
+    self, DirectionalLight, PointLight, RenderMode, RenderSettings,
+};
+use proyect2_raytracing::scene::Scene;
+use proyect2_raytracing::sky::PhysicalSky;
+
+const WIDTH: i32 = 64;
+const HEIGHT: i32 = 48;
+// Diferencia perceptual (YIQ, 0..1) a partir de la cual un píxel cuenta como distinto.
+const PIXEL_THRESHOLD: f32 = 0.05;
+// Fracción de píxeles distintos que se tolera (ruido de punto flotante entre plataformas).
+const MAX_DIFF_RATIO: f32 = 0.005;
+// Máximo de la diferencia YIQ al cuadrado entre blanco y negro.
+const MAX_YIQ_DELTA: f32 = 35215.0;
+
+fn cube(x: f32, y: f32, z: f32, size: f32, yaw: f32, material: Material) -> Object {
+    Object::Cube(Cube::from_center_size_rot(
+        vec3(x, y, z),
+        size,
+        yaw,
+        0.0,
+        0.0,
+        material,
+    ))
+}
+
+fn floor() -> Object {
+    Object::Plane(Plane::new(
+        vec3(0.0, 0.0, 0.0),
+        vec3(0.0, 1.0, 0.0),
+        Material::solid(Rgb::new(120, 170, 90)),
+    ))
+}
+
+fn camera() -> Camera {
+    Camera::new(
+        vec3(0.0, 1.6, 3.2),
+        // `Camera::new` invierte el pitch del objetivo (igual que la cámara de main).
+        vec3(0.0, 2.7, 0.0),
+        vec3(0.0, 1.0, 0.0),
+        60.0,
+        WIDTH as f32 / HEIGHT as f32,
+    )
+}
+
+// Cubos opacos sobre el piso: sombreado directo, sombras y una luz puntual.
+fn cubes() -> Scene {
+    let red = Material::solid(Rgb::new(200, 60, 50));
+    let blue = Material::solid(Rgb::new(60, 90, 210));
+    let yellow = Material::solid(Rgb::new(230, 210, 80));
+    Scene::builder()
+        .with_object(floor())
+        .with_object(cube(-0.8, 0.4, 0.0, 0.8, 20.0, red))
+        .with_object(cube(0.6, 0.3, -0.4, 0.6, -35.0, blue))
+        .with_object(cube(0.1, 0.2, 0.8, 0.4, 0.0, yellow))
+        .with_light(PointLight {
+            pos: vec3(0.0, 1.5, 1.0),
+            color: Rgb::new(255, 200, 150),
+            intensity: 2.0,
+        })
+        .with_settings(RenderSettings {
+            ibl: false,
+            ..RenderSettings::default()
+        })
+        .build()
+}
+
+// Reflexión, refracción y emisión.
+fn materials() -> Scene {
+    let mirror = Material::solid_with(Rgb::new(220, 220, 230), 0.9, 0.8);
+    let glass = Material {
+        transparency: 0.9,
+        ior: 1.5,
+        ..Material::solid_with(Rgb::new(240, 250, 255), 0.5, 0.1)
+    };
+    let red = Material::solid(Rgb::new(200, 60, 50));
+    let glow = Material {
+        emission: Rgb::new(255, 180, 80),
+        emission_strength: 2.0,
+        ..Material::solid(Rgb::new(255, 180, 80))
+    };
+    Scene::builder()
+        .with_objects([
+            floor(),
+            cube(-0.8, 0.4, -0.2, 0.8, 30.0, mirror),
+            cube(0.5, 0.35, 0.3, 0.7, -15.0, glass),
+            cube(0.3, 0.25, -1.2, 0.5, 0.0, glow),
+            cube(-0.2, 0.15, 1.0, 0.3, 45.0, red),
+        ])
+        .with_settings(RenderSettings {
+            ibl: false,
+            ..RenderSettings::default()
+        })
+        .build()
+}
+
+// Cielo físico con su sol e iluminación ambiental (IBL).
+fn sky() -> Scene {
+    let env = Environment::new(EnvSource::Physical(PhysicalSky::new(3.0, 16.0)));
+    let sun = DirectionalLight::from_sky(&env).unwrap_or_default();
+    let mut scene = cubes();
+    scene.lights.clear();
+    scene.environment = Some(env);
+    scene.sun = sun;
+    scene.settings.ibl = true;
+    scene
+}
+
+// Vista de normales de depuración.
+fn normals() -> Scene {
+    let mut scene = cubes();
+    scene.settings.mode = RenderMode::Normals;
+    scene
+}
+
+fn render(scene: &Scene) -> Vec<Rgb> {
+    let mut fb = FrameBuffer::new(WIDTH, HEIGHT, Rgb::new(0, 0, 0));
+    renderer::render_to_fb(&camera(), &mut fb, scene);
+    fb.pixels
+}
+
+fn encode_ppm(width: i32, height: i32, pixels: &[Rgb]) -> Vec<u8> {
+    let mut out = format!("P6\n{width} {height}\n255\n").into_bytes();
+    out.extend(pixels.iter().flat_map(|c| [c.r, c.g, c.b]));
+    out
+}
+
+fn decode_ppm(bytes: &[u8]) -> Result<(i32, i32, Vec<Rgb>), String> {
+    // Cabecera: P6, ancho, alto y máximo separados por blancos; luego un blanco y los datos.
+    let mut fields = vec![];
+    let mut pos = 0;
+    while fields.len() < 4 {
+        while bytes.get(pos).is_some_and(|b| b.is_ascii_whitespace()) {
+            pos += 1;
+        }
+        let start = pos;
+        while bytes.get(pos).is_some_and(|b| !b.is_ascii_whitespace()) {
+            pos += 1;
+        }
+        if start == pos {
+            return Err("cabecera PPM incompleta".into());
+        }
+        fields.push(String::from_utf8_lossy(&bytes[start..pos]).into_owned());
+    }
+    if fields[0] != "P6" || fields[3] != "255" {
+        return Err(format!(
+            "PPM no soportado: {} (máximo {})",
+            fields[0], fields[3]
+        ));
+    }
+    let num = |s: &str| {
+        s.parse::<i32>()
+            .map_err(|e| format!("tamaño PPM '{s}': {e}"))
+    };
+    let (w, h) = (num(&fields[1])?, num(&fields[2])?);
+    let data = bytes.get(pos + 1..).unwrap_or_default();
+    if data.len() < (w * h * 3) as usize {
+        return Err(format!("PPM truncado: {w}x{h}, {} bytes", data.len()));
+    }
+    let pixels = data
+        .chunks_exact(3)
+        .take((w * h) as usize)
+        .map(|p| Rgb::new(p[0], p[1], p[2]))
+        .collect();
+    Ok((w, h, pixels))
+}
+
+fn yiq(c: Rgb) -> (f32, f32, f32) {
+    let (r, g, b) = (c.r as f32, c.g as f32, c.b as f32);
+    (
+        0.29889 * r + 0.58662 * g + 0.11448 * b,
+        0.59598 * r - 0.27418 * g - 0.32180 * b,
+        0.21147 * r - 0.52262 * g + 0.31115 * b,
+    )
+}
+
+// Diferencia perceptual en 0..1 (distancia YIQ ponderada, como en pixelmatch).
+fn perceptual_delta(a: Rgb, b: Rgb) -> f32 {
+    let (ya, ia, qa) = yiq(a);
+    let (yb, ib, qb) = yiq(b);
+    let (dy, di, dq) = (ya - yb, ia - ib, qa - qb);
+    ((0.5053 * dy * dy + 0.299 * di * di + 0.1957 * dq * dq) / MAX_YIQ_DELTA).sqrt()
+}
+
+// Píxeles distintos en rojo sobre la referencia en gris tenue.
+fn diff_image(expected: &[Rgb], actual: &[Rgb]) -> (usize, Vec<Rgb>) {
+    let mut count = 0;
+    let pixels = expected
+        .iter()
+        .zip(actual)
+        .map(|(&e, &a)| {
+            if perceptual_delta(e, a) > PIXEL_THRESHOLD {
+                count += 1;
+                Rgb::new(255, 0, 0)
+            } else {
+                let g = (255.0 - (255.0 - yiq(e).0) * 0.1) as u8;
+                Rgb::new(g, g, g)
+            }
+        })
+        .collect();
+    (count, pixels)
+}
+
+fn check(name: &str, scene: Scene) {
+    let root = env!("CARGO_MANIFEST_DIR");
+    let golden = format!("{root}/tests/golden/{name}.ppm");
+    let actual = render(&scene);
+
+    if std::env::var_os("BLESS").is_some() {
+        std::fs::create_dir_all(format!("{root}/tests/golden")).unwrap();
+        std::fs::write(&golden, encode_ppm(WIDTH, HEIGHT, &actual))
+            .unwrap_or_else(|e| panic!("{golden}: {e}"));
+        return;
+    }
+
+    let bytes =
+        std::fs::read(&golden).unwrap_or_else(|e| panic!("{golden}: {e} (BLESS=1 para generarla)"));
+    let (w, h, expected) = decode_ppm(&bytes).unwrap_or_else(|e| panic!("{golden}: {e}"));
+    assert_eq!((w, h), (WIDTH, HEIGHT), "{golden}: tamaño distinto");
+
+    let (count, diff) = diff_image(&expected, &actual);
+    let ratio = count as f32 / actual.len() as f32;
+    if ratio > MAX_DIFF_RATIO {
+        let out = format!("{root}/target/regresion");
+        std::fs::create_dir_all(&out).unwrap();
+        std::fs::write(format!("{out}/{name}.ppm"), encode_ppm(w, h, &actual)).unwrap();
+        std::fs::write(format!("{out}/{name}_diff.ppm"), encode_ppm(w, h, &diff)).unwrap();
+        panic!(
+            "{name}: {count} píxeles distintos ({:.2}%, máximo {:.2}%); ver {out}/{name}_diff.ppm",
+            ratio * 100.0,
+            MAX_DIFF_RATIO * 100.0
+        );
+    }
+}
+
+#[test]
+fn regresion_cubos() {
+    check("cubos", cubes());
+}
+
+#[test]
+fn regresion_materiales() {
+    check("materiales", materials());
+}
+
+#[test]
+fn regresion_cielo() {
+    check("cielo", sky());
+}
+
+#[test]
+fn regresion_normales() {
+    check("normales", normals());
+}
+
+#[test]
+fn ppm_ida_y_vuelta() {
+    let pixels: Vec<Rgb> = (0..6).map(|i| Rgb::new(i * 40, 255 - i * 40, 7)).collect();
+    let (w, h, back) = decode_ppm(&encode_ppm(3, 2, &pixels)).unwrap();
+    assert_eq!((w, h), (3, 2));
+    assert_eq!(back, pixels);
+}
+
+#[test]
+fn sin_desfase_de_pixel() {
+    // Dos cubos lado a lado en orto, pintados por índice de objeto: el primer píxel de
+    // cada mitad tiene que ser del color de su cubo, no del píxel anterior.
+    let mut cam = Camera::new(
+        vec3(0.0, 0.0, 5.0),
+        vec3(0.0, 0.0, 0.0),
+        vec3(0.0, 1.0, 0.0),
+        60.0,
+        1.0,
+    );
+    cam.projection = Projection::Orthographic;
+    cam.ortho_height = 2.0;
+    let solid = || Material::solid(Rgb::new(128, 128, 128));
+    let scene = Scene::builder()
+        .with_object(cube(-1.0, 0.0, 0.0, 2.0, 0.0, solid()))
+        .with_object(cube(1.0, 0.0, 0.0, 2.0, 0.0, solid()))
+        .with_settings(RenderSettings {
+            mode: RenderMode::ObjectId,
+            ..RenderSettings::default()
+        })
+        .build();
+    let mut fb = FrameBuffer::new(8, 8, Rgb::new(0, 0, 0));
+    renderer::render_to_fb(&cam, &mut fb, &scene);
+    let row = &fb.pixels[8 * 4..8 * 5];
+    assert_eq!(row[0], row[3]);
+    assert_eq!(row[4], row[7]);
+    assert_ne!(row[3], row[4]);
+}
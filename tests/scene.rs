@@ -0,0 +1,124 @@
+// La BVH de `Scene` tiene que dar el mismo impacto que probar todos los objetos.
+use glm::{Vec3, vec3};
+use nalgebra_glm as glm;
+use proyect2_raytracing::color::{Material, Rgb};
+use proyect2_raytracing::intersect::RayIntersect;
+use proyect2_raytracing::objects::cube::euler_rotation;
+use proyect2_raytracing::objects::{Cube, Object, Plane};
+use proyect2_raytracing::sampling::Rng;
+use proyect2_raytracing::scene::Scene;
+
+// Cubos girados de tamaños variados (algunos moviéndose en el obturador) sobre un piso.
+fn cubes(rng: &mut Rng, count: usize) -> Vec<Object> {
+    let mut objects = vec![Object::Plane(Plane::new(
+        vec3(0.0, -2.0, 0.0),
+        vec3(0.0, 1.0, 0.0),
+        Material::solid(Rgb::new(120, 170, 90)),
+    ))];
+    let mut r = move || rng.next_f32();
+    for _ in 0..count {
+        let center = vec3(r() * 10.0 - 5.0, r() * 4.0 - 2.0, r() * 10.0 - 5.0);
+        let mut cube = Cube::from_center_size_rot(
+            center,
+            0.2 + r() * 0.8,
+            r() * 360.0,
+            r() * 360.0,
+            r() * 360.0,
+            Material::solid(Rgb::new(200, 60, 50)),
+        );
+        if r() < 0.2 {
+            let end = center + vec3(r() - 0.5, r() - 0.5, r() - 0.5);
+            cube.set_motion(end, Some(euler_rotation(r() * 90.0, 0.0, 0.0)));
+        }
+        objects.push(Object::Cube(cube));
+    }
+    objects
+}
+
+// Referencia: todos los objetos en orden, a igual distancia gana el primero.
+fn linear(objects: &[Object], ro: &Vec3, rd: &Vec3, time: f32) -> Option<(usize, f32)> {
+    let mut best: Option<(usize, f32)> = None;
+    for (i, o) in objects.iter().enumerate() {
+        let hit = o.ray_intersect_at(ro, rd, time);
+        if hit.is_intersecting && best.is_none_or(|(_, d)| hit.distance < d) {
+            best = Some((i, hit.distance));
+        }
+    }
+    best
+}
+
+fn check_rays(scene: &Scene, rng: &mut Rng, rays: usize) {
+    for _ in 0..rays {
+        let ro = vec3(
+            rng.next_f32() * 16.0 - 8.0,
+            rng.next_f32() * 8.0 - 4.0,
+            rng.next_f32() * 16.0 - 8.0,
+        );
+        let rd = glm::normalize(&vec3(
+            rng.next_f32() - 0.5,
+            rng.next_f32() - 0.5,
+            rng.next_f32() - 0.5,
+        ));
+        let time = rng.next_f32();
+        let (index, hit) = scene.intersect(&ro, &rd, time);
+        let expected = linear(scene.objects(), &ro, &rd, time);
+        assert_eq!(index, expected.map(|(i, _)| i), "rayo {ro:?} -> {rd:?}");
+        if let Some((_, d)) = expected {
+            assert_eq!(hit.distance, d);
+        }
+    }
+}
+
+#[test]
+fn bvh_como_recorrido_lineal() {
+    let mut rng = Rng::new(7);
+    let scene = Scene::builder().with_objects(cubes(&mut rng, 300)).build();
+    check_rays(&scene, &mut rng, 2000);
+}
+
+#[test]
+fn bvh_despues_de_cambiar_objetos() {
+    let mut rng = Rng::new(11);
+    let mut scene = Scene::builder().with_objects(cubes(&mut rng, 100)).build();
+
+    // Quitar sin `commit`: se recorren todos hasta volver a armarla.
+    scene.objects_mut().truncate(60);
+    check_rays(&scene, &mut rng, 500);
+
+    // Mover sin `commit` (la cantidad no cambia) y después volver a armar.
+    for o in scene.objects_mut() {
+        if let Object::Cube(c) = o {
+            c.center += vec3(1.0, 0.5, -1.0);
+        }
+    }
+    check_rays(&scene, &mut rng, 500);
+    scene.commit();
+    check_rays(&scene, &mut rng, 500);
+}
+
+#[test]
+fn objeto_movido_sin_commit() {
+    let material = Material::solid(Rgb::new(200, 60, 50));
+    let cube = |x: f32| {
+        Object::Cube(Cube::from_center_size_rot(
+            vec3(x, 0.0, 0.0),
+            1.0,
+            0.0,
+            0.0,
+            0.0,
+            material.clone(),
+        ))
+    };
+    let mut scene = Scene::builder()
+        .with_objects([cube(0.0), cube(-5.0)])
+        .build();
+    let rd = vec3(0.0, 0.0, 1.0);
+    assert_eq!(scene.intersect(&vec3(0.0, 0.0, -10.0), &rd, 0.0).0, Some(0));
+
+    // Se cambia por otro en otro lugar: la vieja BVH ya no lo encontraría.
+    scene.objects_mut()[0] = cube(5.0);
+    assert_eq!(scene.intersect(&vec3(0.0, 0.0, -10.0), &rd, 0.0).0, None);
+    assert_eq!(scene.intersect(&vec3(5.0, 0.0, -10.0), &rd, 0.0).0, Some(0));
+    scene.commit();
+    assert_eq!(scene.intersect(&vec3(5.0, 0.0, -10.0), &rd, 0.0).0, Some(0));
+}